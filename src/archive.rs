@@ -0,0 +1,170 @@
+use calamine::{Reader, Xlsx};
+use glob::Pattern;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+use crate::error::ExcelReaderError;
+
+
+/// Whether `path` names a .zip archive rather than a workbook.
+pub fn is_archive(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+
+/// Whether `path` is a pattern for several workbooks, e.g. "data/2024-*.xlsx", rather than one file.
+///
+/// A file that exists under that exact name is read as it is.
+pub fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '[']) && !Path::new(path).exists()
+}
+
+/// The files matching a glob pattern, sorted by path so runs read them in the same order.
+pub fn glob_workbooks(pattern: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let paths = glob::glob(pattern).map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path?;
+        if path.is_file() {
+            files.push(path.to_str().ok_or_else(|| format!("Path {:?} is not valid UTF-8", path))?.to_string());
+        }
+    }
+    if files.is_empty() {
+        return Err(ExcelReaderError::FileNotFound(format!("{} (no files match the pattern)", pattern)).into());
+    }
+    files.sort();
+    Ok(files)
+}
+
+
+/// Entries that unpack to more than this are refused, so a zip bomb can't fill memory.
+pub const MAX_ENTRY_BYTES: u64 = 1 << 30;
+
+
+/// The .xlsx/.xlsm entries of a zip archive, read into memory one at a time.
+pub struct ArchiveWorkbooks {
+    zip: ZipArchive<BufReader<File>>,
+    /// `(entry name in the archive, entry index)` in archive order
+    pub entries: Vec<(String, usize)>,
+}
+
+/// Lists the workbooks in a zip archive.
+///
+/// Entries in nested directories are included, `inner_glob` (e.g.
+/// "daily/*.xlsx") is matched against the full entry name, with `*` also
+/// crossing "/". Lock files ("~$...") and macOS metadata are skipped.
+pub fn archive_workbooks(path: &str, inner_glob: Option<&str>) -> Result<ArchiveWorkbooks, Box<dyn Error>> {
+    let inner_glob = inner_glob
+        .map(|glob| Pattern::new(glob).map_err(|e| format!("Invalid --inner-glob pattern '{}': {}", glob, e)))
+        .transpose()?;
+    let file = File::open(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => Box::new(ExcelReaderError::FileNotFound(path.to_string())) as Box<dyn Error>,
+        _ => e.into(),
+    })?;
+    let mut zip = ZipArchive::new(BufReader::new(file))?;
+    let mut entries = Vec::new();
+    for index in 0..zip.len() {
+        let entry = zip.by_index_raw(index)?;
+        let name = entry.name().to_string();
+        let file_name = name.rsplit('/').next().unwrap_or_default();
+        let is_workbook = [".xlsx", ".xlsm"].iter().any(|ext| file_name.to_ascii_lowercase().ends_with(ext));
+        if entry.is_dir() || !is_workbook || file_name.starts_with("~$") || name.starts_with("__MACOSX/") {
+            continue;
+        }
+        if inner_glob.as_ref().is_some_and(|glob| !glob.matches(&name)) {
+            continue;
+        }
+        entries.push((name, index));
+    }
+    if entries.is_empty() {
+        return Err(format!("No .xlsx files found in archive '{}'", path).into());
+    }
+    Ok(ArchiveWorkbooks { zip, entries })
+}
+
+impl ArchiveWorkbooks {
+    /// Reads the entry at `index` and opens it as a workbook.
+    ///
+    /// No more than the size the entry declares is read, and entries
+    /// declaring more than [`MAX_ENTRY_BYTES`] are refused.
+    pub fn open(&mut self, index: usize) -> Result<Xlsx<Cursor<Vec<u8>>>, Box<dyn Error>> {
+        let entry = self.zip.by_index(index)?;
+        let name = entry.name().to_string();
+        let size = entry.size();
+        if size > MAX_ENTRY_BYTES {
+            return Err(format!("Archive entry '{}' unpacks to {} bytes, more than the {} allowed", name, size, MAX_ENTRY_BYTES).into());
+        }
+        let mut bytes = Vec::with_capacity(size as usize);
+        // One byte more than declared shows an entry that lies about its size
+        entry.take(size + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > size {
+            return Err(format!("Archive entry '{}' unpacks to more than the {} bytes it declares", name, size).into());
+        }
+        Ok(Xlsx::new(Cursor::new(bytes)).map_err(|e| format!("Cannot open archive entry '{}' as a workbook: {}", name, e))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_lists_matching_files_in_order() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        for name in ["b.xlsx", "a.xlsx", "notes.txt"] {
+            std::fs::write(dir.path().join(name), "")?;
+        }
+        std::fs::create_dir(dir.path().join("c.xlsx"))?;
+        let pattern = format!("{}/*.xlsx", dir.path().display());
+        assert!(is_glob(&pattern));
+        let files = glob_workbooks(&pattern)?;
+        let names: Vec<&str> = files.iter().map(|file| file.rsplit('/').next().unwrap()).collect();
+        assert_eq!(names, ["a.xlsx", "b.xlsx"]);
+
+        let err = glob_workbooks(&format!("{}/*.xlsm", dir.path().display())).unwrap_err();
+        assert_eq!(err.downcast_ref::<ExcelReaderError>().map(ExcelReaderError::exit_code), Some(10));
+        Ok(())
+    }
+
+    #[test]
+    fn lists_archived_workbooks_matching_a_pattern() -> Result<(), Box<dyn Error>> {
+        use std::io::Write;
+        let dir = tempfile::tempdir()?;
+        let workbook = |id: f64| -> Result<Vec<u8>, rust_xlsxwriter::XlsxError> {
+            let mut book = rust_xlsxwriter::Workbook::new();
+            let sheet = book.add_worksheet();
+            sheet.write_string(0, 0, "id")?;
+            sheet.write_number(1, 0, id)?;
+            book.save_to_buffer()
+        };
+        let path = dir.path().join("daily.zip");
+        let mut zip = ::zip::ZipWriter::new(std::fs::File::create(&path)?);
+        let entries = [
+            ("daily/a.xlsx", workbook(1.0)?),
+            ("daily/2024/b.xlsx", workbook(2.0)?),
+            ("monthly/c.xlsx", workbook(3.0)?),
+            ("daily/~$a.xlsx", Vec::new()),
+            ("daily/readme.txt", b"not a workbook".to_vec()),
+        ];
+        for (name, bytes) in entries {
+            zip.start_file(name, ::zip::write::SimpleFileOptions::default())?;
+            zip.write_all(&bytes)?;
+        }
+        zip.finish()?;
+
+        let path = path.to_str().unwrap();
+        // `*` crosses "/" so nested entries match too
+        let listed = archive_workbooks(path, Some("daily/*.xlsx"))?;
+        let names: Vec<&str> = listed.entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["daily/a.xlsx", "daily/2024/b.xlsx"]);
+        assert!(archive_workbooks(path, Some("daily/[.xlsx")).is_err());
+        assert!(archive_workbooks(path, Some("yearly/*")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use clap::{ArgAction, ArgMatches, Command};
+use clap::parser::ValueSource;
+use std::error::Error;
+use std::ffi::OsString;
+
+
+/// Arguments that only make sense on the command line.
+const CLI_ONLY: &[&str] = &["config", "dump-config", "help", "version"];
+
+
+/// Turns a TOML config file into command line arguments.
+///
+/// Keys are argument names without the leading dashes, e.g. `header = "0,1"`
+/// or `decimal-columns = ["amount=18,2"]`. Settings already given on the
+/// command line are skipped so that flags always win over the file. The
+/// result is meant to be parsed in front of the real command line.
+pub fn config_args(path: &str, cli: &Command, cli_matches: &ArgMatches) -> Result<Vec<OsString>, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read config file '{}': {}", path, e))?;
+    let table: toml::Table = text
+        .parse()
+        .map_err(|e| format!("Invalid config file '{}': {}", path, e))?;
+
+    let mut args = Vec::new();
+    for (key, value) in &table {
+        let arg = cli
+            .get_arguments()
+            .find(|arg| arg.get_id() == key.as_str() && !CLI_ONLY.contains(&key.as_str()))
+            .ok_or_else(|| format!("Unknown setting '{}' in config file '{}'", key, path))?;
+        if cli_matches.value_source(key) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+
+        let flag = format!("--{}", arg.get_long().unwrap_or(key));
+        match (arg.get_action(), value) {
+            (ArgAction::SetTrue, toml::Value::Boolean(true)) => args.push(flag.into()),
+            (ArgAction::SetTrue, toml::Value::Boolean(false)) => {}
+            (ArgAction::SetTrue, _) => {
+                return Err(format!("Setting '{}' in config file must be true or false", key).into());
+            }
+            (ArgAction::Append, toml::Value::Array(items)) => {
+                for item in items {
+                    args.push(format!("{}={}", flag, scalar(key, item)?).into());
+                }
+            }
+            (_, toml::Value::Array(items)) => {
+                let joined = items.iter().map(|item| scalar(key, item)).collect::<Result<Vec<_>, _>>()?;
+                args.push(format!("{}={}", flag, joined.join(",")).into());
+            }
+            (_, value) => args.push(format!("{}={}", flag, scalar(key, value)?).into()),
+        }
+    }
+    Ok(args)
+}
+
+fn scalar(key: &str, value: &toml::Value) -> Result<String, Box<dyn Error>> {
+    match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Integer(i) => Ok(i.to_string()),
+        toml::Value::Float(f) => Ok(f.to_string()),
+        toml::Value::Boolean(b) => Ok(b.to_string()),
+        _ => Err(format!("Setting '{}' in config file must be a string, number or list", key).into()),
+    }
+}
+
+
+/// Renders the given settings as TOML, in the same shape `--config` reads.
+///
+/// Only settings from the command line, or the `--config` file merged into
+/// it, are written. Defaults stay out so that reading the file back doesn't
+/// turn them into explicit settings that conflict with each other.
+pub fn dump_config(cli: &Command, matches: &ArgMatches) -> Result<String, Box<dyn Error>> {
+    let mut table = toml::Table::new();
+    for arg in cli.get_arguments() {
+        let id = arg.get_id().as_str();
+        if CLI_ONLY.contains(&id) || matches.value_source(id) != Some(ValueSource::CommandLine) {
+            continue;
+        }
+        let value = match arg.get_action() {
+            ArgAction::SetTrue => toml::Value::Boolean(matches.get_flag(id)),
+            ArgAction::Append => match matches.get_raw(id) {
+                Some(values) => toml::Value::Array(
+                    values.map(|v| toml::Value::String(v.to_string_lossy().into_owned())).collect(),
+                ),
+                None => continue,
+            },
+            _ => match matches.get_raw(id).and_then(|mut values| values.next()) {
+                Some(value) => toml::Value::String(value.to_string_lossy().into_owned()),
+                None => continue,
+            },
+        };
+        table.insert(id.to_string(), value);
+    }
+    Ok(toml::to_string(&table)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_cli;
+
+    #[test]
+    fn config_file_fills_in_unset_arguments() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        std::fs::write(&path, "header = \"0,1\"\ninfer-types = true\nunion = false\ndecimal-columns = [\"amount=18,2\", \"tax=9,2\"]\n")?;
+        let path = path.to_str().unwrap();
+        let cli = build_cli().try_get_matches_from(["excel_reader", "-p", "a.xlsx", "-t", "2", "--config", path])?;
+        let args = config_args(path, &build_cli(), &cli)?;
+        // --header was given on the command line and wins over the file
+        assert_eq!(args, ["--decimal-columns=amount=18,2", "--decimal-columns=tax=9,2", "--infer-types"]);
+
+        std::fs::write(dir.path().join("bad.toml"), "no-such-setting = 1\n")?;
+        let bad = dir.path().join("bad.toml");
+        assert!(config_args(bad.to_str().unwrap(), &build_cli(), &cli).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn dumped_config_reads_back() -> Result<(), Box<dyn Error>> {
+        let args = ["excel_reader", "-p", "a.xlsx", "-t", "0,1", "--infer-types", "--decimal-columns", "amount=18,2"];
+        let dumped = dump_config(&build_cli(), &build_cli().try_get_matches_from(args)?)?;
+        // Settings left unset, such as --anchor, stay out of the file
+        assert!(!dumped.contains("anchor") && !dumped.contains("all-sheets"));
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("dumped.toml");
+        std::fs::write(&path, &dumped)?;
+        let path = path.to_str().unwrap();
+        let first_pass = build_cli().ignore_errors(true).try_get_matches_from(["excel_reader", "--config", path])?;
+        let file_args = config_args(path, &build_cli(), &first_pass)?;
+        let command_line = std::iter::once("excel_reader".into()).chain(file_args);
+        let reloaded = build_cli().try_get_matches_from(command_line)?;
+        assert_eq!(dump_config(&build_cli(), &reloaded)?, dumped);
+        Ok(())
+    }
+
+    #[test]
+    fn dumped_config_with_a_range_reads_back() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        for args in [&["--range", "B2:C4"][..], &["--anchor", "auto"][..]] {
+            let command_line = ["excel_reader", "-p", "a.xlsx"].iter().chain(args);
+            let dumped = dump_config(&build_cli(), &build_cli().try_get_matches_from(command_line)?)?;
+            let path = dir.path().join("dumped.toml");
+            std::fs::write(&path, &dumped)?;
+            let path = path.to_str().unwrap();
+            let first_pass = build_cli().ignore_errors(true).try_get_matches_from(["excel_reader", "--config", path])?;
+            let file_args = config_args(path, &build_cli(), &first_pass)?;
+            // --anchor has no default to clash with --range on the way back
+            let reloaded = build_cli().try_get_matches_from(std::iter::once("excel_reader".into()).chain(file_args))?;
+            assert_eq!(dump_config(&build_cli(), &reloaded)?, dumped);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use polars::prelude::*;
+use std::error::Error;
+
+use crate::frame::{CellError, ErrorLog};
+
+
+/// How to treat values with more fractional digits than the target scale.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DecimalPolicy {
+    #[default]
+    Error,
+    Round,
+}
+
+/// A `--decimal-columns` entry: cast `column` to `Decimal(precision, scale)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecimalSpec {
+    pub column: String,
+    pub precision: usize,
+    pub scale: usize,
+}
+
+/// Parses a "name=precision,scale" specification.
+pub fn parse_decimal_spec(spec: &str) -> Result<DecimalSpec, Box<dyn Error>> {
+    let (column, rest) = spec
+        .rsplit_once('=')
+        .ok_or_else(|| format!("Invalid decimal spec '{}', expected name=precision,scale", spec))?;
+    let (precision, scale) = rest
+        .split_once(',')
+        .ok_or_else(|| format!("Invalid decimal spec '{}', expected name=precision,scale", spec))?;
+    let precision: usize = precision.trim().parse()?;
+    let scale: usize = scale.trim().parse()?;
+    if precision == 0 || precision > 38 || scale > precision {
+        return Err(format!("Invalid decimal precision/scale in '{}'", spec).into());
+    }
+    Ok(DecimalSpec { column: column.trim().to_string(), precision, scale })
+}
+
+/// Parses a plain decimal string into an integer scaled by `10^scale`.
+///
+/// Empty strings yield `None`. Extra fractional digits are rejected or rounded
+/// half away from zero depending on `policy`.
+pub fn parse_decimal(value: &str, scale: usize, policy: DecimalPolicy) -> Result<Option<i128>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (int_part.is_empty() && frac_part.is_empty()) || !is_digits(int_part) || !is_digits(frac_part) {
+        return Err(format!("'{}' is not a decimal number", value));
+    }
+
+    let overflow = || format!("'{}' is too large for a decimal", value);
+    let mut scaled: i128 = 0;
+    for digit in int_part.bytes().chain(frac_part.bytes().chain(std::iter::repeat(b'0')).take(scale)) {
+        scaled = scaled
+            .checked_mul(10)
+            .and_then(|v| v.checked_add((digit - b'0') as i128))
+            .ok_or_else(overflow)?;
+    }
+    if frac_part.len() > scale {
+        let dropped = &frac_part[scale..];
+        match policy {
+            DecimalPolicy::Error if dropped.bytes().any(|b| b != b'0') => {
+                return Err(format!("'{}' has more than {} fractional digits", value, scale));
+            }
+            DecimalPolicy::Round if dropped.as_bytes()[0] >= b'5' => {
+                scaled = scaled.checked_add(1).ok_or_else(overflow)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(Some(if negative { -scaled } else { scaled }))
+}
+
+/// Formats an integer scaled by `10^scale` as a plain decimal string, the
+/// inverse of [`parse_decimal`]. Every digit is kept, "12.50" stays "12.50".
+pub fn format_decimal(value: i128, scale: usize) -> String {
+    let digits = format!("{:0>width$}", value.unsigned_abs(), width = scale + 1);
+    let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+    let sign = if value < 0 { "-" } else { "" };
+    match scale {
+        0 => format!("{}{}", sign, int_part),
+        _ => format!("{}{}.{}", sign, int_part, frac_part),
+    }
+}
+
+/// Replaces the named string columns with Decimal columns.
+pub(crate) fn cast_decimal_columns(
+    df: &mut DataFrame,
+    specs: &[DecimalSpec],
+    policy: DecimalPolicy,
+    errors: Option<&ErrorLog>,
+    first_row: usize,
+) -> Result<(), Box<dyn Error>> {
+    for spec in specs {
+        let column = df.column(&spec.column)?.str()?;
+        let values = column
+            .into_iter()
+            .enumerate()
+            .map(|(row, v)| match (v.map(|v| parse_decimal(v, spec.scale, policy)), errors) {
+                (Some(Err(reason)), Some(log)) => {
+                    log.push(CellError {
+                        row: first_row + row,
+                        column: spec.column.clone(),
+                        value: v.unwrap_or_default().to_string(),
+                        reason,
+                    });
+                    Ok(None)
+                }
+                (Some(parsed), _) => parsed,
+                (None, _) => Ok(None),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Column '{}': {}", spec.column, e))?;
+        let decimal = Int128Chunked::from_iter_options(spec.column.as_str().into(), values.into_iter())
+            .into_decimal(Some(spec.precision), spec.scale)?;
+        df.with_column(decimal.into_series())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_decimals_with_scale() {
+        assert_eq!(parse_decimal("12.5", 2, DecimalPolicy::Error), Ok(Some(1250)));
+        assert_eq!(parse_decimal("-0.125", 2, DecimalPolicy::Round), Ok(Some(-13)));
+        assert_eq!(parse_decimal("", 2, DecimalPolicy::Error), Ok(None));
+        assert!(parse_decimal("0.125", 2, DecimalPolicy::Error).is_err());
+        assert!(parse_decimal("1,5", 2, DecimalPolicy::Error).is_err());
+    }
+
+    #[test]
+    fn formats_decimals_with_every_digit() {
+        assert_eq!(format_decimal(1250, 2), "12.50");
+        assert_eq!(format_decimal(-5, 3), "-0.005");
+        assert_eq!(format_decimal(42, 0), "42");
+        let large = "12345678901234567890.123456789012345678";
+        assert_eq!(parse_decimal(large, 18, DecimalPolicy::Error).map(|v| format_decimal(v.unwrap(), 18)).as_deref(), Ok(large));
+    }
+}
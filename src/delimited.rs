@@ -0,0 +1,71 @@
+use calamine::{Data, Range};
+use polars::prelude::*;
+use std::error::Error;
+use std::path::Path;
+
+use crate::error::ExcelReaderError;
+
+
+/// The field separator a `.csv` or `.tsv` path implies, `None` for any other file.
+pub fn separator_for(path: &str) -> Option<u8> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "csv" => Some(b','),
+        "tsv" => Some(b'\t'),
+        _ => None,
+    }
+}
+
+
+/// Reads a delimited text file into cells, as if it were a worksheet of text cells.
+///
+/// Polars' CSV reader splits the fields, without taking a header or
+/// inferring types, so header rows and column types are worked out the same
+/// way as for a workbook. Empty fields become empty cells. Rows may differ in
+/// width, e.g. a title line above the table.
+pub fn read_delimited(path: &str, separator: u8) -> Result<Range<Data>, Box<dyn Error>> {
+    let bytes = std::fs::read(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ExcelReaderError::FileNotFound(path.to_string()).into(),
+        _ => Box::<dyn Error>::from(e),
+    })?;
+    // Polars takes the width from the first line, which may be a short title
+    let width = max_fields(&bytes, separator);
+    let schema: Schema = (0..width).map(|i| Field::new(format!("column_{}", i + 1).into(), DataType::String)).collect();
+    let df = CsvReadOptions::default()
+        .with_has_header(false)
+        .with_schema(Some(Arc::new(schema)))
+        .with_parse_options(CsvParseOptions::default().with_separator(separator))
+        .into_reader_with_file_handle(std::io::Cursor::new(bytes))
+        .finish()?;
+    let height = df.height();
+    if height == 0 {
+        return Ok(Range::empty());
+    }
+    let mut range = Range::new((0, 0), (height as u32 - 1, width as u32 - 1));
+    for (col_idx, column) in df.get_columns().iter().enumerate() {
+        for (row_idx, value) in column.str()?.into_iter().enumerate() {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                range.set_value((row_idx as u32, col_idx as u32), Data::String(value.to_string()));
+            }
+        }
+    }
+    Ok(range)
+}
+
+
+/// The most fields any record has, not counting separators and line breaks inside quotes.
+fn max_fields(text: &[u8], separator: u8) -> usize {
+    let (mut widest, mut fields, mut quoted) = (0, 1, false);
+    for &byte in text {
+        match byte {
+            b'"' => quoted = !quoted,
+            b'\n' if !quoted => {
+                widest = widest.max(fields);
+                fields = 1;
+            }
+            byte if byte == separator && !quoted => fields += 1,
+            _ => {}
+        }
+    }
+    widest.max(fields)
+}
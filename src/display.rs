@@ -0,0 +1,587 @@
+use calamine::{Data, ExcelDateTime, ExcelDateTimeType};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::notes::{attribute, open_package, read_part, relationships, sheet_part, workbook_part};
+use crate::workbook::parse_cell_ref;
+
+
+const STYLES_REL: &str = "/relationships/styles";
+
+
+/// Number format codes of the cells of the sheet called exactly `sheet_name`, keyed by 0-based `(row, col)`.
+///
+/// calamine only uses number formats to tell dates from numbers, so this
+/// reads the styles and the sheet's cell style indices from the package.
+/// Cells formatted as "General" are left out.
+pub fn cell_formats(path: &str, sheet_name: &str) -> Result<HashMap<(u32, u32), String>, Box<dyn Error>> {
+    let mut zip = open_package(path)?;
+    let Some(sheet) = sheet_part(&mut zip, sheet_name)? else {
+        return Ok(HashMap::new());
+    };
+    let workbook = workbook_part(&mut zip)?;
+    let styles = relationships(&mut zip, &workbook)?
+        .into_iter()
+        .find(|rel| rel.kind.ends_with(STYLES_REL))
+        .map_or_else(|| "xl/styles.xml".to_string(), |rel| rel.target);
+    let has_styles = zip.by_name(&styles).is_ok();
+    let style_formats = match has_styles {
+        true => parse_style_formats(&read_part(&mut zip, &styles)?)?,
+        false => Vec::new(),
+    };
+
+    let xml = read_part(&mut zip, &sheet)?;
+    let mut reader = Reader::from_str(&xml);
+    let mut formats = HashMap::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"c" => {
+                let style = attribute(&e, b"s", reader.decoder())?.and_then(|s| s.parse::<usize>().ok());
+                let code = style.and_then(|s| style_formats.get(s)).filter(|code| !code.eq_ignore_ascii_case("General"));
+                let position = attribute(&e, b"r", reader.decoder())?.and_then(|r| parse_cell_ref(&r));
+                if let (Some(code), Some(position)) = (code, position) {
+                    formats.insert(position, code.clone());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(formats)
+}
+
+
+/// The number of rows frozen at the top of the sheet called exactly `sheet_name`, if any.
+///
+/// This is the `ySplit` of a frozen `<pane>` in the sheet view, rows
+/// counted from Excel row 1. Panes split without freezing don't count.
+pub fn frozen_rows(path: &str, sheet_name: &str) -> Result<Option<u32>, Box<dyn Error>> {
+    let mut zip = open_package(path)?;
+    let Some(sheet) = sheet_part(&mut zip, sheet_name)? else {
+        return Ok(None);
+    };
+    let xml = read_part(&mut zip, &sheet)?;
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"pane" => {
+                let state = attribute(&e, b"state", reader.decoder())?;
+                if !matches!(state.as_deref(), Some("frozen" | "frozenSplit")) {
+                    return Ok(None);
+                }
+                let rows = attribute(&e, b"ySplit", reader.decoder())?.and_then(|rows| rows.parse::<f64>().ok());
+                return Ok(rows.map(|rows| rows as u32).filter(|rows| *rows > 0));
+            }
+            // The pane, if any, comes before the cells
+            Event::Start(e) if e.name().as_ref() == b"sheetData" => return Ok(None),
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+
+/// Whether the workbook counts serial dates from 1904, as older Mac workbooks do.
+///
+/// This is the `date1904` attribute of the workbook's `<workbookPr>`.
+pub fn uses_1904_dates(path: &str) -> Result<bool, Box<dyn Error>> {
+    let mut zip = open_package(path)?;
+    let workbook = workbook_part(&mut zip)?;
+    let xml = read_part(&mut zip, &workbook)?;
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"workbookPr" => {
+                return Ok(matches!(attribute(&e, b"date1904", reader.decoder())?.as_deref(), Some("1" | "true")));
+            }
+            // The properties, if any, come before the sheets
+            Event::Start(e) if e.name().as_ref() == b"sheets" => return Ok(false),
+            Event::Eof => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+
+/// The 0-based rows hidden in the sheet called exactly `sheet_name`, by row hiding or a filter.
+///
+/// calamine doesn't report row visibility, so this reads the `hidden`
+/// attribute of the sheet's `<row>` elements. Filters hide rows the same way.
+pub fn hidden_rows(path: &str, sheet_name: &str) -> Result<HashSet<u32>, Box<dyn Error>> {
+    let mut zip = open_package(path)?;
+    let Some(sheet) = sheet_part(&mut zip, sheet_name)? else {
+        return Ok(HashSet::new());
+    };
+    let xml = read_part(&mut zip, &sheet)?;
+    let mut reader = Reader::from_str(&xml);
+    let mut hidden = HashSet::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"row" => {
+                let is_hidden = matches!(attribute(&e, b"hidden", reader.decoder())?.as_deref(), Some("1" | "true"));
+                let row = attribute(&e, b"r", reader.decoder())?.and_then(|r| r.parse::<u32>().ok());
+                if let (true, Some(row)) = (is_hidden, row) {
+                    hidden.insert(row.saturating_sub(1));
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"sheetData" => break,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(hidden)
+}
+
+
+/// The format code of every cell style, indexed like the `s` attribute of cells.
+fn parse_style_formats(xml: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    let mut custom: HashMap<u32, String> = HashMap::new();
+    let mut ids = Vec::new();
+    // Only <xf> inside <cellXfs> are cell styles, <cellStyleXfs> holds named styles
+    let mut in_cell_xfs = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                b"numFmt" => {
+                    let id = attribute(&e, b"numFmtId", reader.decoder())?.and_then(|id| id.parse().ok());
+                    let code = attribute(&e, b"formatCode", reader.decoder())?;
+                    if let (Some(id), Some(code)) = (id, code) {
+                        custom.insert(id, code);
+                    }
+                }
+                b"cellXfs" => in_cell_xfs = true,
+                b"xf" if in_cell_xfs => {
+                    let id = attribute(&e, b"numFmtId", reader.decoder())?.and_then(|id| id.parse().ok());
+                    ids.push(id.unwrap_or(0));
+                }
+                _ => {}
+            },
+            Event::End(e) if e.name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(ids
+        .into_iter()
+        .map(|id| custom.get(&id).cloned().unwrap_or_else(|| builtin_format(id).to_string()))
+        .collect())
+}
+
+
+/// The codes of Excel's built-in number formats, "General" for unknown ids.
+fn builtin_format(id: u32) -> &'static str {
+    match id {
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        12 => "# ?/?",
+        13 => "# ??/??",
+        14 => "mm-dd-yy",
+        15 => "d-mmm-yy",
+        16 => "d-mmm",
+        17 => "mmm-yy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "m/d/yy h:mm",
+        37 => "#,##0 ;(#,##0)",
+        38 => "#,##0 ;[Red](#,##0)",
+        39 => "#,##0.00;(#,##0.00)",
+        40 => "#,##0.00;[Red](#,##0.00)",
+        45 => "mm:ss",
+        46 => "[h]:mm:ss",
+        47 => "mmss.0",
+        48 => "##0.0E+0",
+        49 => "@",
+        _ => "General",
+    }
+}
+
+
+/// The text Excel shows for a number or date cell with the format `code`.
+///
+/// Covers digit placeholders (`0`, `#`, `?`) with literals between them as
+/// in "000-000-0000", thousands separators, percentages, sections for
+/// negative numbers and zero, and date/time codes including elapsed `[h]`.
+/// Gives `None`, i.e. keep the value, for text and boolean cells and for
+/// scientific and fraction formats. Plain numbers with a date format count
+/// from 1904 when `is_1904` is set, date cells know their own date system.
+pub fn format_cell(cell: &Data, code: &str, is_1904: bool) -> Option<String> {
+    let value = match cell {
+        Data::Float(v) => *v,
+        Data::Int(v) => *v as f64,
+        Data::DateTime(dt) => dt.as_f64(),
+        _ => return None,
+    };
+    let sections = split_sections(code);
+    let (section, value, signed) = match sections.len() {
+        n if n >= 2 && value < 0.0 => (sections[1].as_str(), -value, false),
+        n if n >= 3 && value == 0.0 => (sections[2].as_str(), value, false),
+        _ => (sections[0].as_str(), value, true),
+    };
+    if section.eq_ignore_ascii_case("General") {
+        return None;
+    }
+    let tokens = tokenize(section)?;
+    if tokens.iter().any(|t| matches!(t, Token::Date(_) | Token::Elapsed(_))) {
+        let datetime = match cell {
+            Data::DateTime(dt) => dt.as_datetime()?,
+            _ => ExcelDateTime::new(value, ExcelDateTimeType::DateTime, is_1904).as_datetime()?,
+        };
+        return Some(format_datetime(&tokens, datetime, value));
+    }
+    format_number(&tokens, value, signed)
+}
+
+
+/// Splits a format code on the ";" between its sections, outside quotes.
+fn split_sections(code: &str) -> Vec<String> {
+    let mut sections = vec![String::new()];
+    let mut chars = code.chars();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        let section = sections.last_mut().unwrap();
+        match c {
+            ';' if !quoted => sections.push(String::new()),
+            '"' => {
+                quoted = !quoted;
+                section.push(c);
+            }
+            '\\' => {
+                section.push(c);
+                section.extend(chars.next());
+            }
+            c => section.push(c),
+        }
+    }
+    sections
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Literal(String),
+    /// `0`, `#` or `?`
+    Digit(char),
+    Point,
+    Comma,
+    Percent,
+    /// A run of one date code letter like "yyyy" or "mm", or "ampm"/"ap"
+    Date(String),
+    /// `[h]`, `[m]` or `[s]`, counted from zero instead of wrapping
+    Elapsed(char),
+}
+
+
+/// Splits one section into tokens, `None` for parts that aren't supported.
+fn tokenize(section: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = section.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        let token = match c {
+            '"' => {
+                let end = chars[i..].iter().position(|&c| c == '"').map_or(chars.len(), |p| i + p);
+                let text = chars[i..end].iter().collect();
+                i = end + 1;
+                Token::Literal(text)
+            }
+            '\\' => {
+                i += 1;
+                Token::Literal(chars.get(i - 1).map(|c| c.to_string()).unwrap_or_default())
+            }
+            // Padding as wide as the next character, and fill to the column width
+            '_' => {
+                i += 1;
+                Token::Literal(" ".to_string())
+            }
+            '*' => {
+                i += 1;
+                continue;
+            }
+            '[' => {
+                let end = chars[i..].iter().position(|&c| c == ']').map_or(chars.len(), |p| i + p);
+                let inner: String = chars[i..end].iter().collect::<String>().to_ascii_lowercase();
+                i = end + 1;
+                match inner.chars().next() {
+                    Some(unit @ ('h' | 'm' | 's')) if inner.chars().all(|c| c == unit) => Token::Elapsed(unit),
+                    // Colors, conditions and locales don't change the text
+                    _ => continue,
+                }
+            }
+            '0' | '#' | '?' => Token::Digit(c),
+            '.' => Token::Point,
+            ',' => Token::Comma,
+            '%' => Token::Percent,
+            'E' | 'e' if matches!(chars.get(i), Some('+' | '-')) => return None,
+            '@' => return None,
+            _ if section[section.char_indices().nth(i - 1)?.0..].to_ascii_lowercase().starts_with("am/pm") => {
+                i += 4;
+                Token::Date("ampm".to_string())
+            }
+            _ if section[section.char_indices().nth(i - 1)?.0..].to_ascii_lowercase().starts_with("a/p") => {
+                i += 2;
+                Token::Date("ap".to_string())
+            }
+            'y' | 'Y' | 'm' | 'M' | 'd' | 'D' | 'h' | 'H' | 's' | 'S' => {
+                let lower = c.to_ascii_lowercase();
+                let mut run = lower.to_string();
+                while chars.get(i).is_some_and(|c| c.to_ascii_lowercase() == lower) {
+                    run.push(lower);
+                    i += 1;
+                }
+                Token::Date(run)
+            }
+            c => Token::Literal(c.to_string()),
+        };
+        tokens.push(token);
+    }
+    let is_date = tokens.iter().any(|t| matches!(t, Token::Date(_) | Token::Elapsed(_)));
+    let is_fraction = tokens.iter().any(|t| matches!(t, Token::Literal(s) if s == "/"));
+    if !is_date && is_fraction && tokens.iter().any(|t| matches!(t, Token::Digit(_))) {
+        return None;
+    }
+    Some(tokens)
+}
+
+
+fn format_number(tokens: &[Token], value: f64, signed: bool) -> Option<String> {
+    let point = tokens.iter().position(|t| *t == Token::Point).unwrap_or(tokens.len());
+    let int_digits: Vec<(usize, char)> = tokens[..point]
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| match t {
+            Token::Digit(d) => Some((i, *d)),
+            _ => None,
+        })
+        .collect();
+    let frac_digits: Vec<char> = tokens[point..]
+        .iter()
+        .filter_map(|t| match t {
+            Token::Digit(d) => Some(*d),
+            _ => None,
+        })
+        .collect();
+    let last_digit = tokens.iter().rposition(|t| matches!(t, Token::Digit(_)));
+    // A comma between digit placeholders groups thousands, commas right after the last one scale by 1000
+    let grouping = match (int_digits.first(), int_digits.last()) {
+        (Some(&(first, _)), Some(&(last, _))) => tokens[first..last].contains(&Token::Comma),
+        _ => false,
+    };
+    let scale = last_digit.map_or(0, |last| {
+        tokens[last + 1..].iter().take_while(|t| **t == Token::Comma).count()
+    });
+
+    let mut value = value;
+    for t in tokens {
+        if *t == Token::Percent {
+            value *= 100.0;
+        }
+    }
+    value /= 1000f64.powi(scale as i32);
+    let rounded = format!("{:.*}", frac_digits.len(), value.abs());
+    let (int_text, frac_text) = rounded.split_once('.').unwrap_or((&rounded, ""));
+    let min_int = int_digits.iter().filter(|(_, d)| *d == '0').count();
+    let mut int_text = int_text.trim_start_matches('0').to_string();
+    while int_text.len() < min_int {
+        int_text.insert(0, '0');
+    }
+    if grouping {
+        int_text = group_thousands(&int_text);
+    }
+
+    // Digits go into the integer placeholders from the right, the leftmost one takes any extra
+    let mut int_parts: HashMap<usize, String> = HashMap::new();
+    let mut remaining: Vec<char> = int_text.chars().collect();
+    for (n, &(idx, kind)) in int_digits.iter().enumerate().rev() {
+        let part = if grouping {
+            // Separators make the digits one block, placed at the first placeholder
+            match n {
+                0 => remaining.drain(..).collect(),
+                _ => String::new(),
+            }
+        } else if n == 0 {
+            let part: String = remaining.drain(..).collect();
+            match (part.is_empty(), kind) {
+                (true, '?') => " ".to_string(),
+                _ => part,
+            }
+        } else {
+            match remaining.pop() {
+                Some(digit) => digit.to_string(),
+                None if kind == '?' => " ".to_string(),
+                None => String::new(),
+            }
+        };
+        int_parts.insert(idx, part);
+    }
+
+    // Trailing zeros are dropped where the placeholder is optional
+    let mut frac: Vec<Option<char>> = frac_text.chars().map(Some).collect();
+    for (i, kind) in frac_digits.iter().enumerate().rev() {
+        if *kind == '0' || frac[i] != Some('0') {
+            break;
+        }
+        frac[i] = if *kind == '?' { Some(' ') } else { None };
+    }
+
+    let mut text = String::new();
+    let mut frac_idx = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Literal(s) => text.push_str(s),
+            Token::Digit(_) if i < point => text.push_str(&int_parts[&i]),
+            Token::Digit(_) => {
+                text.extend(frac.get(frac_idx).copied().flatten());
+                frac_idx += 1;
+            }
+            Token::Point => text.push('.'),
+            Token::Percent => text.push('%'),
+            Token::Comma | Token::Date(_) | Token::Elapsed(_) => {}
+        }
+    }
+    let is_zero = rounded.chars().all(|c| c == '0' || c == '.');
+    if signed && value < 0.0 && !is_zero {
+        text.insert(0, '-');
+    }
+    Some(text)
+}
+
+
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+
+fn format_datetime(tokens: &[Token], datetime: NaiveDateTime, serial: f64) -> String {
+    let twelve_hour = tokens.iter().any(|t| matches!(t, Token::Date(d) if d.starts_with('a')));
+    let shows_fraction = tokens.windows(2).any(|w| w[0] == Token::Point && w[1] == Token::Digit('0'));
+    // Without fractional seconds Excel rounds to the nearest second
+    let datetime = match shows_fraction {
+        true => datetime,
+        false => datetime + chrono::Duration::milliseconds(500),
+    };
+    let total_seconds = (serial * 86_400.0).round() as i64;
+    let date_tokens: Vec<(usize, &str)> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| match t {
+            Token::Date(d) => Some((i, d.as_str())),
+            Token::Elapsed('h') => Some((i, "h")),
+            Token::Elapsed('m') => Some((i, "m")),
+            Token::Elapsed(_) => Some((i, "s")),
+            _ => None,
+        })
+        .collect();
+    // "m" means minutes right after an hour or right before a second code
+    let is_minutes = |i: usize| {
+        let pos = date_tokens.iter().position(|(idx, _)| *idx == i).unwrap();
+        let after_hour = pos > 0 && date_tokens[pos - 1].1.starts_with('h');
+        let before_second = date_tokens.get(pos + 1).is_some_and(|(_, d)| d.starts_with('s'));
+        after_hour || before_second
+    };
+
+    let mut text = String::new();
+    let mut tokens_iter = tokens.iter().enumerate().peekable();
+    while let Some((i, token)) = tokens_iter.next() {
+        match token {
+            Token::Literal(s) => text.push_str(s),
+            Token::Point if shows_fraction => {
+                let mut places = 0;
+                while tokens_iter.next_if(|(_, t)| **t == Token::Digit('0')).is_some() {
+                    places += 1;
+                }
+                let fraction = datetime.nanosecond() as f64 / 1e9;
+                let digits = format!("{:.*}", places, fraction);
+                text.push_str(digits.trim_start_matches('0'));
+            }
+            Token::Point => text.push('.'),
+            Token::Digit(d) => text.push(*d),
+            Token::Comma => text.push(','),
+            Token::Percent => text.push('%'),
+            Token::Elapsed('h') => text.push_str(&(total_seconds / 3600).to_string()),
+            Token::Elapsed('m') => text.push_str(&(total_seconds / 60).to_string()),
+            Token::Elapsed(_) => text.push_str(&total_seconds.to_string()),
+            Token::Date(d) => {
+                let hour = match (twelve_hour, datetime.hour() % 12) {
+                    (true, 0) => 12,
+                    (true, h) => h,
+                    (false, _) => datetime.hour(),
+                };
+                let month = datetime.month0() as usize;
+                let part = match d.as_str() {
+                    "y" | "yy" => format!("{:02}", datetime.year() % 100),
+                    d if d.starts_with('y') => format!("{:04}", datetime.year()),
+                    "m" | "mm" if is_minutes(i) => format!("{:0width$}", datetime.minute(), width = d.len()),
+                    "m" => datetime.month().to_string(),
+                    "mm" => format!("{:02}", datetime.month()),
+                    "mmm" => MONTHS[month][..3].to_string(),
+                    "mmmmm" => MONTHS[month][..1].to_string(),
+                    d if d.starts_with('m') => MONTHS[month].to_string(),
+                    "d" => datetime.day().to_string(),
+                    "dd" => format!("{:02}", datetime.day()),
+                    "ddd" => datetime.format("%a").to_string(),
+                    d if d.starts_with('d') => datetime.format("%A").to_string(),
+                    "h" => hour.to_string(),
+                    d if d.starts_with('h') => format!("{:02}", hour),
+                    "s" => datetime.second().to_string(),
+                    d if d.starts_with('s') => format!("{:02}", datetime.second()),
+                    "ampm" => (if datetime.hour() < 12 { "AM" } else { "PM" }).to_string(),
+                    _ => (if datetime.hour() < 12 { "A" } else { "P" }).to_string(),
+                };
+                text.push_str(&part);
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_cells_like_excel_shows_them() {
+        let show = |cell: Data, code: &str| format_cell(&cell, code, false);
+        assert_eq!(show(Data::Float(5551234567.0), "(000) 000-0000").as_deref(), Some("(555) 123-4567"));
+        assert_eq!(show(Data::Int(42), "\"ID-\"00000").as_deref(), Some("ID-00042"));
+        assert_eq!(show(Data::Float(1234567.891), "#,##0.00").as_deref(), Some("1,234,567.89"));
+        assert_eq!(show(Data::Float(-1234.5), "#,##0.00;[Red](#,##0.00)").as_deref(), Some("(1,234.50)"));
+        assert_eq!(show(Data::Float(-3.0), "0.0").as_deref(), Some("-3.0"));
+        assert_eq!(show(Data::Float(0.256), "0.0%").as_deref(), Some("25.6%"));
+        assert_eq!(show(Data::Float(2.5), "#.##").as_deref(), Some("2.5"));
+        let at = |serial: f64| Data::DateTime(ExcelDateTime::new(serial, ExcelDateTimeType::DateTime, false));
+        assert_eq!(show(at(45413.75), "dd.mm.yyyy hh:mm").as_deref(), Some("01.05.2024 18:00"));
+        assert_eq!(show(at(45413.75), "d mmm yy h:mm AM/PM").as_deref(), Some("1 May 24 6:00 PM"));
+        assert_eq!(show(at(1.5), "[h]:mm").as_deref(), Some("36:00"));
+        // Plain numbers with a date format count from the workbook's epoch
+        assert_eq!(show(Data::Float(45413.0), "yyyy-mm-dd").as_deref(), Some("2024-05-01"));
+        assert_eq!(format_cell(&Data::Float(43951.0), "yyyy-mm-dd", true).as_deref(), Some("2024-05-01"));
+        assert_eq!(show(Data::Float(1.0), "0.00E+00"), None);
+        assert_eq!(show(Data::String("x".into()), "0.00"), None);
+    }
+}
@@ -0,0 +1,73 @@
+use std::error::Error;
+use thiserror::Error;
+
+
+/// Failures that map to a dedicated process exit code.
+///
+/// The codes start at 10, clear of 2, which clap exits with on usage errors.
+///
+/// | Code | Meaning                    |
+/// |------|----------------------------|
+/// | 1    | any other error            |
+/// | 2    | invalid command line       |
+/// | 10   | input file not found       |
+/// | 11   | worksheet not found        |
+/// | 12   | header row out of bounds   |
+/// | 13   | output could not be written|
+/// | 14   | headers differ from schema |
+/// | 15   | `--timeout` exceeded       |
+/// | 16   | shape differs from asserted|
+/// | 17   | key values are not unique  |
+#[derive(Debug, Error)]
+pub enum ExcelReaderError {
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+
+    #[error("Worksheet '{0}' not found")]
+    SheetNotFound(String),
+
+    /// `index` counts from the first used row, which is Excel row `first_row`.
+    #[error(
+        "Header row index {index} (Excel row {}) is out of bounds, the sheet has {rows} rows, Excel rows {first_row} to {}",
+        *.first_row as usize + .index,
+        (*.first_row as usize + .rows).saturating_sub(1)
+    )]
+    HeaderOutOfBounds { index: usize, rows: usize, first_row: u32 },
+
+    #[error("Headers do not match the expected schema: {0}")]
+    SchemaMismatch(String),
+
+    #[error("Failed to write output '{path}': {source}")]
+    OutputWrite {
+        path: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    #[error("Processing did not finish within {0} seconds")]
+    Timeout(u64),
+
+    #[error("DataFrame shape does not match: {0}")]
+    ShapeMismatch(String),
+
+    #[error("Key values are not unique: {0}")]
+    DuplicateKeys(String),
+}
+
+impl ExcelReaderError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ExcelReaderError::FileNotFound(_) => 10,
+            ExcelReaderError::SheetNotFound(_) => 11,
+            ExcelReaderError::HeaderOutOfBounds { .. } => 12,
+            ExcelReaderError::OutputWrite { .. } => 13,
+            ExcelReaderError::SchemaMismatch(_) => 14,
+            ExcelReaderError::Timeout(_) => 15,
+            ExcelReaderError::ShapeMismatch(_) => 16,
+            ExcelReaderError::DuplicateKeys(_) => 17,
+        }
+    }
+
+    pub fn output_write(path: &str, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        ExcelReaderError::OutputWrite { path: path.to_string(), source: source.into() }
+    }
+}
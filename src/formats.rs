@@ -0,0 +1,182 @@
+use calamine::{Data, DataType as _, ExcelDateTime, ExcelDateTimeType};
+use chrono::{Datelike, Timelike};
+use polars::prelude::*;
+
+
+/// `NaiveDate::num_days_from_ce` of 1970-01-01, where Polars dates count from.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+
+/// Column type suggested by the number format of its cells.
+///
+/// calamine marks cells whose number format is a date, time or duration
+/// format as `Data::DateTime`, so the format survives even though the
+/// stored value is just a serial number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FormatHint {
+    Date,
+    Time,
+    Datetime,
+    Duration,
+}
+
+
+/// The day serial dates count from, chosen with `--date-system`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DateSystem {
+    /// What the workbook declares: 1900, unless it sets `date1904`
+    #[default]
+    Auto,
+    /// Day 1 is 1900-01-01
+    Excel1900,
+    /// Day 0 is 1904-01-01, used by older Mac workbooks
+    Excel1904,
+}
+
+/// Re-reads the serial date cells of `rows` in `system`, for workbooks that declare the wrong one.
+///
+/// Durations don't depend on the epoch and keep their value.
+pub fn apply_date_system(rows: &mut [Vec<Data>], system: DateSystem) {
+    let is_1904 = match system {
+        DateSystem::Auto => return,
+        DateSystem::Excel1900 => false,
+        DateSystem::Excel1904 => true,
+    };
+    for cell in rows.iter_mut().flatten() {
+        if let Data::DateTime(dt) = cell {
+            let kind = if dt.is_duration() { ExcelDateTimeType::TimeDelta } else { ExcelDateTimeType::DateTime };
+            *dt = ExcelDateTime::new(dt.as_f64(), kind, is_1904);
+        }
+    }
+}
+
+
+/// Picks a hint for every column whose non-empty cells are all date/time formatted.
+pub fn detect_format_hints(data_rows: &[Vec<Data>], width: usize) -> Vec<Option<FormatHint>> {
+    (0..width)
+        .map(|col_idx| {
+            let mut cells = data_rows
+                .iter()
+                .filter_map(|row| row.get(col_idx))
+                .filter(|cell| !matches!(cell, Data::Empty))
+                .peekable();
+            cells.peek()?;
+
+            let mut hint: Option<FormatHint> = None;
+            for cell in cells {
+                let cell_hint = cell_format_hint(cell)?;
+                hint = Some(match (hint, cell_hint) {
+                    (None, h) => h,
+                    (Some(a), b) if a == b => a,
+                    // Mixing whole days with timestamps still fits a datetime column
+                    (Some(FormatHint::Date | FormatHint::Datetime), FormatHint::Date | FormatHint::Datetime) => {
+                        FormatHint::Datetime
+                    }
+                    _ => return None,
+                });
+            }
+            hint
+        })
+        .collect()
+}
+
+/// The hint for a single cell, if it holds a date, time or duration.
+///
+/// Serial values (`Data::DateTime`) are classified from their type and
+/// value, ISO 8601 text (`Data::DateTimeIso`, e.g. from .ods files) from
+/// which parts it has.
+pub fn cell_format_hint(cell: &Data) -> Option<FormatHint> {
+    match cell {
+        Data::DateTime(dt) => Some(serial_hint(dt)),
+        Data::DateTimeIso(text) if text.contains('T') => Some(FormatHint::Datetime),
+        Data::DateTimeIso(_) if cell.as_date().is_some() => Some(FormatHint::Date),
+        Data::DateTimeIso(_) if cell.as_time().is_some() => Some(FormatHint::Time),
+        Data::DurationIso(_) => Some(FormatHint::Duration),
+        _ => None,
+    }
+}
+
+fn serial_hint(dt: &ExcelDateTime) -> FormatHint {
+    let value = dt.as_f64();
+    if dt.is_duration() {
+        FormatHint::Duration
+    } else if (0.0..1.0).contains(&value) {
+        FormatHint::Time
+    } else if value.fract() == 0.0 {
+        FormatHint::Date
+    } else {
+        FormatHint::Datetime
+    }
+}
+
+
+/// Replaces the string columns that have a hint with typed temporal columns.
+///
+/// Columns are matched by position, so `df` must have been built from
+/// `data_rows` without reordering. Cells that aren't date/time formatted
+/// become null.
+pub fn apply_format_hints(
+    df: &mut DataFrame,
+    data_rows: &[Vec<Data>],
+    hints: &[Option<FormatHint>],
+) -> PolarsResult<()> {
+    for (col_idx, hint) in hints.iter().enumerate() {
+        let Some(hint) = hint else { continue };
+        let name = df.get_columns()[col_idx].name().clone();
+        // Only temporal cells are converted, calamine's helpers would also read plain numbers as serial dates
+        let cells = data_rows.iter().map(|row| row.get(col_idx).filter(|cell| cell_format_hint(cell).is_some()));
+        let series = match hint {
+            FormatHint::Date => Int32Chunked::from_iter_options(
+                name,
+                cells.map(|cell| cell?.as_date().map(|date| date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE)),
+            )
+            .into_date()
+            .into_series(),
+            FormatHint::Datetime => Int64Chunked::from_iter_options(
+                name,
+                cells.map(|cell| cell?.as_datetime().map(|d| d.and_utc().timestamp_millis())),
+            )
+            .into_datetime(TimeUnit::Milliseconds, None)
+            .into_series(),
+            FormatHint::Time => Int64Chunked::from_iter_options(
+                name,
+                cells.map(|cell| cell?.as_time().map(time_nanos)),
+            )
+            .into_time()
+            .into_series(),
+            FormatHint::Duration => Int64Chunked::from_iter_options(
+                name,
+                cells.map(|cell| cell?.as_duration().map(|d| d.num_milliseconds())),
+            )
+            .into_duration(TimeUnit::Milliseconds)
+            .into_series(),
+        };
+        df.replace_column(col_idx, series)?;
+    }
+    Ok(())
+}
+
+fn time_nanos(time: chrono::NaiveTime) -> i64 {
+    time.num_seconds_from_midnight() as i64 * 1_000_000_000 + time.nanosecond() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::create_dataframe;
+
+    #[test]
+    fn iso_text_types_like_serial_values() -> Result<(), Box<dyn std::error::Error>> {
+        let rows = vec![
+            vec![Data::DateTimeIso("2024-05-01".into()), Data::DateTimeIso("2024-05-01T06:20:00".into())],
+            vec![Data::Empty, Data::DateTimeIso("2024-05-02T13:00:00".into())],
+        ];
+        let hints = detect_format_hints(&rows, 2);
+        assert_eq!(hints, vec![Some(FormatHint::Date), Some(FormatHint::Datetime)]);
+        let mut df = create_dataframe(vec!["day".into(), "at".into()], &rows, false)?;
+        apply_format_hints(&mut df, &rows, &hints)?;
+        assert_eq!(df.column("day")?.get(0)?, AnyValue::Date(19844));
+        assert_eq!(df.column("at")?.datetime()?.get(1), Some(1_714_654_800_000));
+        Ok(())
+    }
+}
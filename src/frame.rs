@@ -0,0 +1,825 @@
+use calamine::{Data, Range};
+use polars::prelude::*;
+use rayon::prelude::*;
+use regex::Regex;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::decimal::{self, DecimalPolicy, DecimalSpec};
+use crate::error::ExcelReaderError;
+use crate::formats::{self, DateSystem, FormatHint};
+use crate::infer::{self, BigIntPolicy, BoolTokens, InferredType};
+use crate::sheet::{self, AutoHeader, HeaderCollapse, SheetData, SheetPackage};
+use crate::workbook::{FormulaMode, SheetMatch};
+
+
+/// Everything that controls how sheet rows are turned into a DataFrame.
+///
+/// The command line fills these from its arguments, so library callers get
+/// the same conversion for the same settings.
+#[derive(Clone, Debug, Default)]
+pub struct FrameOptions {
+    /// Worksheet [`read_excel_with_meta`] reads, matched like `--worksheet`, or the first one
+    pub sheet: Option<String>,
+    /// 0-based header rows from the first used row, the first row when empty.
+    /// The command line passes its header rows per sheet instead.
+    pub header_rows: Vec<usize>,
+    /// Field separator of .csv or .tsv input, `None` for workbooks
+    pub separator: Option<u8>,
+    pub sheet_match: SheetMatch,
+    pub exclude_sheets: Vec<String>,
+    /// `--sheet-range` as 0-based positions, the end exclusive and `None` for the last sheet
+    pub sheet_range: Option<(usize, Option<usize>)>,
+    pub normalize_cells: bool,
+    pub formatted_values: Option<Vec<String>>,
+    pub max_cell_length: Option<usize>,
+    pub formulas: FormulaMode,
+    pub strict_headers: bool,
+    pub keep_header_in_data: bool,
+    pub header_gap_as_data: bool,
+    pub header_collapse: HeaderCollapse,
+    pub header_separator: Option<String>,
+    pub unnamed_prefix: Option<String>,
+    pub unnamed_by_letter: bool,
+    pub ascii_headers: bool,
+    pub header_from_frozen: bool,
+    /// Leave out data rows hidden in the sheet
+    pub visible_only: bool,
+    /// Find the header row of each sheet read
+    pub auto_header: Option<AutoHeader>,
+    pub numeric_header_prefix: Option<String>,
+    pub header_renames: Vec<(Regex, String)>,
+    pub header_map: Vec<(String, String)>,
+    pub rename_strict: bool,
+    pub currency_symbols: Vec<String>,
+    pub thousands_separator: Option<String>,
+    pub last_row: Option<u32>,
+    pub cell_range: Option<((u32, u32), (u32, u32))>,
+    pub auto_anchor: bool,
+    pub coalesce_headers: bool,
+    pub header_fill_forward: bool,
+    /// Header rows to fill forward, 0 being the top one
+    pub ffill_header_rows: Vec<usize>,
+    pub units_row: Option<usize>,
+    /// Type date/time formatted columns from their number formats
+    pub use_formats: bool,
+    pub date_system: DateSystem,
+    /// Convert text columns whose values are all integers or floats
+    pub infer_types: bool,
+    pub empty_as_null: bool,
+    pub strict_numeric: bool,
+    pub schema_from_first_row: bool,
+    pub mixed_dates_threshold: Option<f64>,
+    pub big_ints: BigIntPolicy,
+    pub bools: BoolTokens,
+    pub expected_headers: Option<Vec<String>>,
+    pub decimal_columns: Vec<DecimalSpec>,
+    pub decimal_policy: DecimalPolicy,
+    pub explode: Vec<ExplodeSpec>,
+    pub row_hash: Option<String>,
+    pub index_column: Option<String>,
+    pub index_first: bool,
+    pub errors: Option<ErrorLog>,
+}
+
+
+/// A cell whose value could not be converted and was left null.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellError {
+    /// 0-based data row
+    pub row: usize,
+    pub column: String,
+    pub value: String,
+    pub reason: String,
+}
+
+/// Collects conversion failures for `--errors-to` instead of failing the run.
+///
+/// Clones share the same list, so the log can travel inside `FrameOptions`.
+#[derive(Clone, Debug, Default)]
+pub struct ErrorLog(Arc<Mutex<Vec<CellError>>>);
+
+impl ErrorLog {
+    pub fn push(&self, error: CellError) {
+        self.0.lock().unwrap().push(error);
+    }
+
+    /// The collected cells, one row each with its `row`, `column`, `value` and `reason`.
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let errors = self.0.lock().unwrap();
+        DataFrame::new(vec![
+            Column::new("row".into(), errors.iter().map(|e| e.row as u64).collect::<Vec<_>>()),
+            Column::new("column".into(), errors.iter().map(|e| e.column.as_str()).collect::<Vec<_>>()),
+            Column::new("value".into(), errors.iter().map(|e| e.value.as_str()).collect::<Vec<_>>()),
+            Column::new("reason".into(), errors.iter().map(|e| e.reason.as_str()).collect::<Vec<_>>()),
+        ])
+    }
+}
+
+
+/// What [`read_excel_with_meta`] found out while reading.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadMeta {
+    /// Name of the worksheet that was read
+    pub sheet: String,
+    /// The header rows used, 0-based from the first used row, also when `auto_header` or `header_from_frozen` found them
+    pub header_rows: Vec<usize>,
+    /// Each column's header text as the sheet has it, and the unique name it got
+    pub header_map: Vec<(String, String)>,
+    /// Each column's name and type, in DataFrame order
+    pub dtypes: Vec<(String, DataType)>,
+    /// Each column's unique name and why it got its type, as `--type-report` gives it
+    pub type_reasons: Vec<(String, String)>,
+}
+
+
+/// Reads a worksheet into a DataFrame, along with the sheet, headers and types it ended up with.
+///
+/// This is the command line's reading of the sheet: the header rows, the
+/// types and the DataFrame are the ones it would give for the same options.
+/// Without `header_rows` the header is the first row, or the one
+/// `auto_header` or `header_from_frozen` finds.
+///
+/// ```no_run
+/// use excel_reader::{FrameOptions, read_excel_with_meta};
+///
+/// let options = FrameOptions { header_rows: vec![2], infer_types: true, ..Default::default() };
+/// let (df, meta) = read_excel_with_meta("report.xlsx", &options)?;
+/// println!("{} rows from '{}': {:?}", df.height(), meta.sheet, meta.dtypes);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn read_excel_with_meta(path: &str, options: &FrameOptions) -> Result<(DataFrame, ReadMeta), Box<dyn Error>> {
+    let sheet = sheet::resolve_sheet_name(path, options.sheet.as_deref(), None, options)?;
+    let given = (!options.header_rows.is_empty()).then(|| options.header_rows.clone());
+    let parts = sheet::worksheet_parts(path, options.sheet.as_deref(), given, options)?;
+    let header_rows = parts.header_rows.clone().unwrap_or(vec![0]);
+    let data = sheet::sheet_from_range(&parts.range, parts.header_rows, &parts.package, options)?;
+    validate_headers(&data.headers, options)?;
+    let plan = plan_types(&data, options)?;
+    let df = convert_rows(&data.headers, data.data_rows(), 0, &plan, options)?;
+
+    let names = process_headers(data.headers.clone());
+    let meta = ReadMeta {
+        sheet,
+        header_rows,
+        type_reasons: names.iter().cloned().zip(plan.reasons.into_iter().map(|reason| reason.reason)).collect(),
+        header_map: data.headers.into_iter().zip(names).collect(),
+        dtypes: df.schema().iter().map(|(name, dtype)| (name.to_string(), dtype.clone())).collect(),
+    };
+    Ok((df, meta))
+}
+
+
+/// Turns cells you already read with calamine into a DataFrame, the way the command line does.
+///
+/// It splits off the header rows with [`sheet::sheet_from_range`] and types
+/// the columns with [`sheet_to_dataframe`], the two steps every worksheet
+/// the command line reads goes through. `header_rows` count from the first
+/// row of `range` and `sheet` is not used. `formatted_values` and
+/// `visible_only` need the workbook file and have no effect here.
+///
+/// ```no_run
+/// use calamine::{Reader, open_workbook_auto};
+/// use excel_reader::{FrameOptions, range_to_dataframe};
+///
+/// let range = open_workbook_auto("report.xlsx")?.worksheet_range("Data")?;
+/// let df = range_to_dataframe(&range, &FrameOptions { infer_types: true, ..Default::default() })?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn range_to_dataframe(range: &Range<Data>, options: &FrameOptions) -> Result<DataFrame, Box<dyn Error>> {
+    let sheet = sheet::sheet_from_range(range, Some(header_rows(options)), &SheetPackage::default(), options)?;
+    sheet_to_dataframe(&sheet, options)
+}
+
+
+/// The header rows to use, the first row when none are given.
+fn header_rows(options: &FrameOptions) -> Vec<usize> {
+    if options.header_rows.is_empty() { vec![0] } else { options.header_rows.clone() }
+}
+
+
+/// Types the columns of `sheet` and builds its DataFrame, the last step of every input.
+pub fn sheet_to_dataframe(sheet: &SheetData, options: &FrameOptions) -> Result<DataFrame, Box<dyn Error>> {
+    validate_headers(&sheet.headers, options)?;
+    let plan = plan_types(sheet, options)?;
+    convert_rows(&sheet.headers, sheet.data_rows(), 0, &plan, options)
+}
+
+
+/// Per-column type decisions, made once for a whole sheet or its first chunk.
+#[derive(Clone, Debug, Default)]
+pub struct TypePlan {
+    pub hints: Vec<Option<FormatHint>>,
+    pub inferred: Vec<Option<InferredType>>,
+    /// Why each column gets its type, for `--type-report`
+    pub reasons: Vec<TypeReason>,
+    /// Made from the first `--chunksize` chunk, so later values may not fit
+    pub first_chunk: bool,
+}
+
+/// How a column's type was decided: the non-empty values looked at and why.
+#[derive(Clone, Debug, Default)]
+pub struct TypeReason {
+    pub sampled: usize,
+    pub reason: String,
+}
+
+impl TypeReason {
+    fn new(sampled: usize, reason: impl Into<String>) -> Self {
+        TypeReason { sampled, reason: reason.into() }
+    }
+}
+
+
+pub fn plan_types(sheet: &SheetData, options: &FrameOptions) -> Result<TypePlan, Box<dyn Error>> {
+    let width = sheet.headers.len();
+    let index = index_column(sheet, options)?;
+    if options.schema_from_first_row {
+        let mut plan = first_row_plan(sheet, options);
+        if let Some(index) = index {
+            plan.hints[index] = None;
+            plan.inferred[index] = None;
+            plan.reasons[index] = TypeReason::new(0, "--index-col keeps it as text");
+        }
+        check_mixed_dates(sheet, &plan.hints, options)?;
+        return Ok(plan);
+    }
+    let mut hints = if options.use_formats {
+        formats::detect_format_hints(sheet.data_rows(), width)
+    } else {
+        Vec::new()
+    };
+    if let Some(hint) = index.and_then(|index| hints.get_mut(index)) {
+        *hint = None;
+    }
+
+    check_mixed_dates(sheet, &hints, options)?;
+
+    let mut inferred = Vec::new();
+    let mut reasons = Vec::with_capacity(width);
+    let names = process_headers(sheet.headers.clone());
+    for (col_idx, name) in names.iter().enumerate() {
+        let hint = hints.get(col_idx).copied().flatten();
+        let is_decimal = options.decimal_columns.iter().any(|spec| &spec.column == name);
+        let reason = if index == Some(col_idx) {
+            TypeReason::new(0, "--index-col keeps it as text")
+        } else if let Some(hint) = hint {
+            let filled = sheet.data_rows().iter().filter(|row| !matches!(row.get(col_idx), None | Some(Data::Empty))).count();
+            TypeReason::new(filled, format!("every value is {:?} formatted", hint).to_lowercase())
+        } else if is_decimal {
+            TypeReason::new(0, "--decimal-columns sets it")
+        } else if !options.infer_types {
+            TypeReason::new(0, "kept as text, --infer-types is off")
+        } else {
+            TypeReason::new(0, "")
+        };
+        if !options.infer_types {
+            reasons.push(reason);
+            continue;
+        }
+        if hint.is_some() || is_decimal || index == Some(col_idx) {
+            inferred.push(None);
+            reasons.push(reason);
+            continue;
+        }
+        let values: Vec<String> = sheet
+            .data_rows()
+            .iter()
+            .map(|row| row.get(col_idx).map(|cell| cell.to_string()).unwrap_or_default())
+            .collect();
+        let has_tokens = !options.bools.truthy.is_empty() || !options.bools.falsy.is_empty();
+        if has_tokens && options.bools.is_bool_column(name, &values) {
+            inferred.push(Some(InferredType::Bool));
+            let filled = values.iter().filter(|value| !value.trim().is_empty()).count();
+            reasons.push(TypeReason::new(filled, "every value is a --bool-true or --bool-false token"));
+            continue;
+        }
+        let inference = infer::explain_column_type(name, &values, options.strict_numeric, options.big_ints)?;
+        inferred.push(Some(inference.ty));
+        reasons.push(TypeReason::new(inference.sampled, inference.reason));
+    }
+    Ok(TypePlan { hints, inferred, reasons, first_chunk: false })
+}
+
+
+/// Position of the `--index-col` column, which stays text whatever its values look like.
+fn index_column(sheet: &SheetData, options: &FrameOptions) -> Result<Option<usize>, Box<dyn Error>> {
+    let Some(index) = &options.index_column else { return Ok(None) };
+    let names = process_headers(sheet.headers.clone());
+    match names.iter().position(|name| name == index) {
+        Some(position) => Ok(Some(position)),
+        None => Err(format!("--index-col '{}' is not a column, available columns: [{}]", index, names.join(", ")).into()),
+    }
+}
+
+
+/// Types every column like its cell in the first data row, for `--schema-from-first-row`.
+///
+/// Numbers give numeric columns and date/time formatted cells temporal ones,
+/// anything else, including an empty first cell, keeps the column as text.
+fn first_row_plan(sheet: &SheetData, options: &FrameOptions) -> TypePlan {
+    let first = sheet.data_rows().first();
+    let names = process_headers(sheet.headers.clone());
+    let mut plan = TypePlan::default();
+    for (col_idx, name) in names.iter().enumerate() {
+        let cell = first.and_then(|row| row.get(col_idx));
+        let is_decimal = options.decimal_columns.iter().any(|spec| &spec.column == name);
+        let (hint, inferred) = match cell {
+            _ if is_decimal => (None, None),
+            Some(Data::Int(_)) => (None, Some(InferredType::Int)),
+            Some(Data::Float(_)) => (None, Some(InferredType::Float)),
+            Some(cell @ (Data::DateTime(_) | Data::DateTimeIso(_) | Data::DurationIso(_))) => (formats::cell_format_hint(cell), None),
+            _ => (None, None),
+        };
+        let reason = match cell {
+            _ if is_decimal => TypeReason::new(0, "--decimal-columns sets it"),
+            None | Some(Data::Empty) => TypeReason::new(0, "--schema-from-first-row: the first data cell is empty"),
+            Some(cell) => TypeReason::new(1, format!("--schema-from-first-row: the first data cell is '{}'", cell)),
+        };
+        plan.hints.push(hint);
+        plan.inferred.push(inferred);
+        plan.reasons.push(reason);
+    }
+    plan
+}
+
+
+/// Fails when a date column has more values that aren't dates than `--abort-on-mixed-dates` allows.
+///
+/// A date column is one typed from its cells (`hints`) or, with
+/// `--use-formats`, one left as text because a minority of its values
+/// aren't dates. The first would null those values, the second loses the
+/// date type, both quietly.
+fn check_mixed_dates(sheet: &SheetData, hints: &[Option<FormatHint>], options: &FrameOptions) -> Result<(), Box<dyn Error>> {
+    let Some(threshold) = options.mixed_dates_threshold else { return Ok(()) };
+    let names = process_headers(sheet.headers.clone());
+    for (col_idx, name) in names.iter().enumerate() {
+        let mut dates = 0;
+        let mut others = Vec::new();
+        for (row_idx, row) in sheet.data_rows().iter().enumerate() {
+            match row.get(col_idx) {
+                None | Some(Data::Empty) => {}
+                Some(cell) if formats::cell_format_hint(cell).is_some() => dates += 1,
+                Some(cell) => others.push((row_idx, cell.to_string())),
+            }
+        }
+        let typed = matches!(hints.get(col_idx), Some(Some(_)));
+        let is_index = options.index_column.as_ref() == Some(name);
+        let is_date_column = typed || (options.use_formats && dates > others.len() && !is_index);
+        let share = others.len() as f64 / (dates + others.len()).max(1) as f64;
+        if is_date_column && share > threshold {
+            let (row, value) = &others[0];
+            return Err(format!(
+                "Column '{}' holds dates, but {} of {} values ({:.1}%) are not, e.g. '{}' in data row {}; \
+                fix the cells or raise the --abort-on-mixed-dates threshold",
+                name,
+                others.len(),
+                dates + others.len(),
+                share * 100.0,
+                value,
+                row,
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+
+/// Typed cells that came out null although the sheet had a value.
+///
+/// Decimal columns are skipped, their casting reports its own reasons.
+fn nulled_cells(df: &DataFrame, rows: &[Vec<Data>], first_row: usize, options: &FrameOptions) -> Vec<CellError> {
+    let mut nulled = Vec::new();
+    for (col_idx, column) in df.get_columns().iter().enumerate() {
+        let is_decimal = options.decimal_columns.iter().any(|spec| spec.column == column.name().as_str());
+        if column.dtype() == &DataType::String || is_decimal || column.null_count() == 0 {
+            continue;
+        }
+        let nulls = column.is_null();
+        for (row_idx, row) in rows.iter().enumerate() {
+            let value = row.get(col_idx).map(|cell| cell.to_string()).unwrap_or_default();
+            if nulls.get(row_idx) == Some(true) && !value.trim().is_empty() {
+                nulled.push(CellError {
+                    row: first_row + row_idx,
+                    column: column.name().to_string(),
+                    value,
+                    reason: format!("not a valid {}", column.dtype()),
+                });
+            }
+        }
+    }
+    nulled
+}
+
+
+/// Builds a DataFrame from data rows and applies the typing options.
+///
+/// `first_row` is the index of `rows[0]` among all data rows, used to report
+/// failed cells to `options.errors`.
+pub fn convert_rows(
+    headers: &[String],
+    rows: &[Vec<Data>],
+    first_row: usize,
+    plan: &TypePlan,
+    options: &FrameOptions,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let mut df = create_dataframe(headers.to_vec(), rows, options.empty_as_null)?;
+    if let Some(name) = &options.row_hash {
+        if df.get_column_index(name).is_some() {
+            return Err(format!("--row-hash column '{}' already exists, pick another name", name).into());
+        }
+        df.with_column(Column::new(name.as_str().into(), row_hashes(rows, headers.len())))?;
+    }
+    formats::apply_format_hints(&mut df, rows, &plan.hints)?;
+    infer::apply_inferred_types(&mut df, &plan.inferred, &options.bools)?;
+    decimal::cast_decimal_columns(&mut df, &options.decimal_columns, options.decimal_policy, options.errors.as_ref(), first_row)?;
+    if options.errors.is_some() || options.schema_from_first_row || plan.first_chunk {
+        let nulled = nulled_cells(&df, rows, first_row, options);
+        match &options.errors {
+            Some(log) => nulled.into_iter().for_each(|error| log.push(error)),
+            None => {
+                // Only --schema-from-first-row and the first chunk's types are forced onto values that may not fit
+                let source = match options.schema_from_first_row {
+                    true => "the first row's type",
+                    false => "the type of the first --chunksize rows",
+                };
+                let mut columns: Vec<(&str, usize)> = Vec::new();
+                for error in &nulled {
+                    match columns.iter_mut().find(|(name, _)| *name == error.column) {
+                        Some((_, count)) => *count += 1,
+                        None => columns.push((&error.column, 1)),
+                    }
+                }
+                for (column, count) in columns {
+                    eprintln!("Warning: column '{}': {} values don't match {} and were left empty", column, count, source);
+                }
+            }
+        }
+    }
+    for spec in &options.explode {
+        df = explode_delimited(&df, spec)?;
+    }
+    if let (Some(index), true) = (&options.index_column, options.index_first) {
+        let index = df.drop_in_place(index)?;
+        df.insert_column(0, index)?;
+    }
+    Ok(df)
+}
+
+
+/// Checks the header names, before deduplication, against `--validate-schema`.
+///
+/// The error lists missing and unexpected columns, or both orders when the
+/// names match but are out of order.
+pub fn validate_headers(headers: &[String], options: &FrameOptions) -> Result<(), ExcelReaderError> {
+    let Some(expected) = &options.expected_headers else { return Ok(()) };
+    if headers == expected.as_slice() {
+        return Ok(());
+    }
+    let missing: Vec<&String> = expected.iter().filter(|name| !headers.contains(name)).collect();
+    let added: Vec<&String> = headers.iter().filter(|name| !expected.contains(name)).collect();
+    let mut problems = Vec::new();
+    if !missing.is_empty() {
+        problems.push(format!("missing {:?}", missing));
+    }
+    if !added.is_empty() {
+        problems.push(format!("unexpected {:?}", added));
+    }
+    if problems.is_empty() {
+        problems.push(format!("columns reordered, expected {:?}, found {:?}", expected, headers));
+    }
+    Err(ExcelReaderError::SchemaMismatch(problems.join("; ")))
+}
+
+
+/// Hashes every row's cells for `--row-hash`, in the text form `create_dataframe` gives them.
+///
+/// The hash is XXH3-64 over the first `width` cells in column order, each as
+/// its byte length (u64, little-endian) followed by its UTF-8 text, so
+/// moving text between neighbouring cells changes it. Missing and empty
+/// cells both count as "", and the hash does not depend on any typing option.
+fn row_hashes(rows: &[Vec<Data>], width: usize) -> Vec<u64> {
+    rows.par_iter()
+        .map(|row| {
+            let mut hasher = Xxh3::new();
+            let mut buffer = String::new();
+            for i in 0..width {
+                let text = cell_text(row.get(i), &mut buffer);
+                hasher.update(&(text.len() as u64).to_le_bytes());
+                hasher.update(text.as_bytes());
+            }
+            hasher.digest()
+        })
+        .collect()
+}
+
+
+/// An `--explode-delimited` entry: split `column` on `separator` into rows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExplodeSpec {
+    pub column: String,
+    pub separator: String,
+}
+
+/// Parses a "column:separator" specification.
+pub fn parse_explode_spec(spec: &str) -> Result<ExplodeSpec, Box<dyn Error>> {
+    match spec.split_once(':') {
+        Some((column, separator)) if !column.is_empty() && !separator.is_empty() => Ok(ExplodeSpec {
+            column: column.to_string(),
+            separator: separator.to_string(),
+        }),
+        _ => Err(format!("Invalid explode spec '{}', expected column:separator", spec).into()),
+    }
+}
+
+
+/// Splits a text column on a separator and gives every value its own row.
+///
+/// Segments are trimmed and empty ones dropped. Rows without any value keep a
+/// single null so they don't disappear, the other columns are repeated.
+pub fn explode_delimited(df: &DataFrame, spec: &ExplodeSpec) -> Result<DataFrame, Box<dyn Error>> {
+    let column = df.column(&spec.column)?;
+    let values = column
+        .str()
+        .map_err(|_| format!("--explode-delimited column '{}' must be a text column", spec.column))?;
+    let mut lists: ListChunked = values
+        .into_iter()
+        .map(|value| {
+            let segments: Vec<&str> = value?
+                .split(spec.separator.as_str())
+                .map(str::trim)
+                .filter(|segment| !segment.is_empty())
+                .collect();
+            (!segments.is_empty()).then(|| Series::new(PlSmallStr::EMPTY, segments))
+        })
+        .collect();
+    lists.rename(column.name().clone());
+    // Without any values the list type can't be inferred, keep it text for empty inputs
+    let lists = lists.into_series().cast(&DataType::List(Box::new(DataType::String)))?;
+
+    let mut df = df.clone();
+    df.with_column(lists)?;
+    Ok(df.explode([spec.column.as_str()])?)
+}
+
+
+/// Processes a vector of header names to ensure uniqueness.
+///
+/// This function takes a vector of strings representing header names and processes them
+/// to ensure that each header name is unique. If a header name is empty, it is replaced
+/// with a default name in the format "Unnamed_{index}", where {index} is the position
+/// of the header in the input vector. If a header name already exists in the processed
+/// list, a suffix is appended to the name to make it unique, following the format
+/// "{base_name}_{suffix}".
+///
+/// # Parameters
+///
+/// - `headers`: A vector of strings containing the header names to be processed.
+///
+/// # Returns
+///
+/// A vector of strings containing the processed header names, ensuring that all names
+/// are unique. If there were any duplicates or empty names in the input, they will be
+/// modified accordingly.
+///
+/// # Example
+///
+/// ```
+/// # use excel_reader::frame::process_headers;
+/// let headers = vec!["Header1".to_string(), "".to_string(), "Header1".to_string()];
+/// let processed = process_headers(headers);
+/// assert_eq!(processed, vec!["Header1", "Unnamed_1", "Header1_1"]);
+/// ```
+///
+/// # Panics
+///
+/// This function does not panic under normal circumstances, but it assumes that the
+/// input vector is not excessively large, as it uses a hash set to track used names.
+/// 
+/// # Complexity
+///
+/// The function has a time complexity of O(n) where n is the number of headers, as it
+/// iterates through the list and performs constant-time operations for each header.
+pub fn process_headers(headers: Vec<String>) -> Vec<String> {
+    let mut processed_headers = Vec::with_capacity(headers.len());
+    let mut used_names = PlHashSet::new();
+
+    for (i, header) in headers.iter().enumerate() {
+        let base_name = if header.is_empty() {
+            format!("Unnamed_{}", i)
+        } else {
+            header.clone()
+        };
+
+        let mut candidate = base_name.clone();
+        let mut suffix = 0;
+
+        // Generate a unique candidate name
+        while used_names.contains(&candidate) {
+            suffix += 1;
+            candidate = format!("{}_{}", base_name, suffix);
+        }
+
+        used_names.insert(candidate.clone());
+        processed_headers.push(candidate);
+    }
+    processed_headers
+}
+
+
+/// The text of a cell, formatted into `buffer` unless it already is text.
+///
+/// Missing cells, past the end of a short row, read as empty.
+pub fn cell_text<'a>(cell: Option<&'a Data>, buffer: &'a mut String) -> &'a str {
+    match cell {
+        Some(Data::String(text)) => text,
+        None | Some(Data::Empty) => "",
+        Some(cell) => {
+            buffer.clear();
+            write!(buffer, "{}", cell).expect("writing to a String cannot fail");
+            buffer
+        }
+    }
+}
+
+
+/// Builds one text column per header straight from the cells.
+///
+/// Each cell is formatted once into a reused buffer and copied into its
+/// column, text cells are copied as they are. Rows shorter than the header
+/// are padded with empty strings, cells beyond it are ignored. With
+/// `empty_as_null`, empty text becomes null instead.
+pub fn create_dataframe(headers: Vec<String>, rows: &[Vec<Data>], empty_as_null: bool) -> Result<DataFrame, PolarsError> {
+    let headers = process_headers(headers);
+    // An indexed parallel collect keeps the input order, so columns always
+    // follow the headers whatever the thread count
+    let columns: Vec<Column> = headers
+        .par_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let mut builder = StringChunkedBuilder::new(name.as_str().into(), rows.len());
+            let mut buffer = String::new();
+            for row in rows {
+                match cell_text(row.get(i), &mut buffer) {
+                    "" if empty_as_null => builder.append_null(),
+                    text => builder.append_value(text),
+                }
+            }
+            builder.finish().into_column()
+        })
+        .collect();
+    DataFrame::new(columns)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_hashes_follow_cell_order_and_boundaries() {
+        let text = |values: &[&str]| values.iter().map(|v| Data::String(v.to_string())).collect::<Vec<_>>();
+        let rows = vec![text(&["a", "bc"]), text(&["ab", "c"]), text(&["bc", "a"]), text(&["a", "bc", "ignored"]), text(&["1"])];
+        let hashes = row_hashes(&rows, 2);
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert_eq!(hashes[0], hashes[3]);
+        assert_eq!(hashes[4], row_hashes(&[vec![Data::Int(1), Data::Empty]], 2)[0]);
+    }
+
+    #[test]
+    fn converts_calamine_ranges_to_dataframes() -> Result<(), Box<dyn Error>> {
+        let cell = |row, col, value: Data| calamine::Cell::new((row, col), value);
+        let range = Range::from_sparse(vec![
+            cell(4, 1, Data::String("Region".into())),
+            cell(4, 2, Data::String("Region".into())),
+            cell(5, 1, Data::String("North".into())),
+            cell(5, 2, Data::Float(1.5)),
+        ]);
+        let options = FrameOptions { infer_types: true, ..Default::default() };
+        let df = range_to_dataframe(&range, &options)?;
+        assert_eq!(df.get_column_names_str(), ["Region", "Region_1"]);
+        assert_eq!(df.column("Region_1")?.dtype(), &DataType::Float64);
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_date_columns_abort_above_threshold() {
+        let date = || Data::DateTime(calamine::ExcelDateTime::new(45413.0, calamine::ExcelDateTimeType::DateTime, false));
+        let sheet = SheetData {
+            headers: vec!["day".into()],
+            rows: vec![vec![date()], vec![date()], vec![Data::String("n/a".into())], vec![date()]],
+            data_start: 0,
+        };
+        let options = |threshold| FrameOptions { use_formats: true, mixed_dates_threshold: threshold, ..Default::default() };
+        // Without the check the column quietly stays text
+        assert!(plan_types(&sheet, &options(None)).is_ok());
+        let err = plan_types(&sheet, &options(Some(0.0))).err().unwrap().to_string();
+        assert!(err.contains("'n/a' in data row 2"), "{}", err);
+        assert!(plan_types(&sheet, &options(Some(0.25))).is_ok());
+    }
+
+    #[test]
+    fn column_order_does_not_depend_on_thread_count() -> Result<(), Box<dyn Error>> {
+        let headers: Vec<String> = (0..64).map(|i| format!("col{}", 63 - i)).collect();
+        let data = vec![headers.iter().map(|h| Data::String(h.clone())).collect::<Vec<_>>(); 100];
+        let build = |threads: usize| -> Result<DataFrame, Box<dyn Error>> {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+            Ok(pool.install(|| create_dataframe(headers.clone(), &data, false))?)
+        };
+        let single = build(1)?;
+        assert_eq!(single.get_column_names_str(), headers.iter().map(|h| h.as_str()).collect::<Vec<_>>());
+        for threads in [2, 8, 32] {
+            assert_eq!(build(threads)?.schema(), single.schema());
+        }
+        Ok(())
+    }
+
+    /// Timing for a wide sheet, run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn create_dataframe_wide_sheet_benchmark() -> Result<(), Box<dyn Error>> {
+        let (width, height, runs) = (200, 20_000, 3);
+        let headers: Vec<String> = (0..width).map(|i| format!("col{}", i)).collect();
+        let rows: Vec<Vec<Data>> = (0..height)
+            .map(|r| {
+                (0..width)
+                    .map(|c| match (r + c) % 4 {
+                        0 => Data::Float(r as f64 * 0.5),
+                        1 => Data::String(format!("text {}", r)),
+                        2 => Data::Int(r as i64),
+                        _ => Data::Empty,
+                    })
+                    .collect()
+            })
+            .collect();
+        let start = std::time::Instant::now();
+        for _ in 0..runs {
+            assert_eq!(create_dataframe(headers.clone(), &rows, false)?.shape(), (height, width));
+        }
+        println!("create_dataframe {}x{}: {:?} per run", height, width, start.elapsed() / runs as u32);
+        Ok(())
+    }
+
+    #[test]
+    fn explodes_delimited_values_into_rows() -> Result<(), Box<dyn Error>> {
+        let df = df!("id" => [1, 2, 3], "tags" => [Some("a; b;"), Some(" "), None])?;
+        let exploded = explode_delimited(&df, &parse_explode_spec("tags:;")?)?;
+        let ids: Vec<i32> = exploded.column("id")?.i32()?.into_no_null_iter().collect();
+        assert_eq!(ids, [1, 1, 2, 3]);
+        let tags: Vec<Option<&str>> = exploded.column("tags")?.str()?.into_iter().collect();
+        assert_eq!(tags, [Some("a"), Some("b"), None, None]);
+        assert!(explode_delimited(&df, &parse_explode_spec("id:;")?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn failed_cells_are_left_empty_and_logged() -> Result<(), Box<dyn Error>> {
+        let text = |s: &str| Data::String(s.to_string());
+        let sheet = SheetData {
+            headers: vec!["id".into(), "amount".into()],
+            rows: vec![
+                vec![text("id"), text("amount")],
+                vec![Data::Float(1.0), text("1.50")],
+                vec![text("x"), text("1.234")],
+                vec![Data::Float(3.0), text("2")],
+            ],
+            data_start: 1,
+        };
+        let errors = ErrorLog::default();
+        let options = FrameOptions {
+            schema_from_first_row: true,
+            decimal_columns: vec![decimal::parse_decimal_spec("amount=9,2")?],
+            errors: Some(errors.clone()),
+            ..Default::default()
+        };
+        let df = sheet_to_dataframe(&sheet, &options)?;
+        assert_eq!(df.column("id")?.f64()?.to_vec(), [Some(1.0), None, Some(3.0)]);
+        assert_eq!(df.column("amount")?.null_count(), 1);
+
+        let log = errors.to_dataframe()?;
+        let column = |name: &str| -> PolarsResult<Vec<String>> {
+            Ok(log.column(name)?.cast(&DataType::String)?.str()?.into_no_null_iter().map(String::from).collect())
+        };
+        assert_eq!(column("row")?, ["1", "1"]);
+        assert_eq!(column("column")?, ["amount", "id"]);
+        assert_eq!(column("value")?, ["1.234", "x"]);
+        assert_eq!(column("reason")?, ["'1.234' has more than 2 fractional digits", "not a valid f64"]);
+        Ok(())
+    }
+
+    #[test]
+    fn schema_validation_names_the_differences() {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let options = FrameOptions { expected_headers: Some(names(&["id", "name"])), ..Default::default() };
+        assert!(validate_headers(&names(&["id", "name"]), &options).is_ok());
+        let err = validate_headers(&names(&["id", "email"]), &options).unwrap_err();
+        assert_eq!(err.to_string(), "Headers do not match the expected schema: missing [\"name\"]; unexpected [\"email\"]");
+        let err = validate_headers(&names(&["name", "id"]), &options).unwrap_err();
+        assert!(err.to_string().contains("columns reordered"));
+        assert_eq!(err.exit_code(), 14);
+    }
+}
@@ -0,0 +1,263 @@
+use polars::prelude::*;
+
+
+/// Column type picked by inference from the cell texts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InferredType {
+    Int,
+    Float,
+    Bool,
+    Text,
+}
+
+
+/// What inference does with whole numbers too large for a 64-bit integer,
+/// such as 20-digit account numbers.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BigIntPolicy {
+    /// Keep the column as text, with a warning
+    #[default]
+    String,
+    /// Accept a float column even though digits are lost
+    F64,
+    /// Fail
+    Error,
+}
+
+
+/// Picks the narrowest type every non-empty value parses as.
+///
+/// Numeric types are only chosen when each value survives a round trip, i.e.
+/// formatting the parsed number gives back the original text. Values like
+/// "007" or "1.10" would be silently changed by a numeric column, so they keep
+/// the column as text with a warning, or fail when `strict` is set. Integers
+/// beyond the i64 range are handled as `big_ints` says.
+pub fn infer_column_type<I, S>(column: &str, values: I, strict: bool, big_ints: BigIntPolicy) -> Result<InferredType, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    explain_column_type(column, values, strict, big_ints).map(|inference| inference.ty)
+}
+
+
+/// A column's inferred type and how inference got there, for `--type-report`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inference {
+    pub ty: InferredType,
+    /// Non-empty values looked at before deciding
+    pub sampled: usize,
+    /// e.g. "mixed: number '3' at data row 1 and text 'n/a' at data row 42"
+    pub reason: String,
+}
+
+
+/// Like [`infer_column_type`], also saying why, with 1-based data row numbers.
+pub fn explain_column_type<I, S>(column: &str, values: I, strict: bool, big_ints: BigIntPolicy) -> Result<Inference, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut candidate: Option<InferredType> = None;
+    let mut first_number: Option<(usize, String)> = None;
+    let mut first_fraction: Option<(usize, String)> = None;
+    let mut lossy: Option<(usize, String)> = None;
+    let mut big: Option<(usize, String)> = None;
+    let mut sampled = 0;
+    for (row, value) in values.into_iter().enumerate() {
+        let value = value.as_ref().trim();
+        if value.is_empty() {
+            continue;
+        }
+        sampled += 1;
+        let row = row + 1;
+        let (parsed, round_trips) = if let Ok(v) = value.parse::<i64>() {
+            (InferredType::Int, v.to_string() == value)
+        } else if is_whole_number(value) {
+            if big.is_none() && big_ints != BigIntPolicy::F64 {
+                big = Some((row, value.to_string()));
+            }
+            (InferredType::Float, true)
+        } else if let Ok(v) = value.parse::<f64>() {
+            if first_fraction.is_none() {
+                first_fraction = Some((row, value.to_string()));
+            }
+            (InferredType::Float, v.is_finite() && v.to_string() == value)
+        } else {
+            let reason = match first_number {
+                Some((at, number)) => format!("mixed: number '{}' at data row {} and text '{}' at data row {}", number, at, value, row),
+                None => format!("text '{}' at data row {}", value, row),
+            };
+            return Ok(Inference { ty: InferredType::Text, sampled, reason });
+        };
+        first_number.get_or_insert_with(|| (row, value.to_string()));
+        if !round_trips && lossy.is_none() {
+            lossy = Some((row, value.to_string()));
+        }
+        candidate = Some(match (candidate, parsed) {
+            (None, ty) => ty,
+            (Some(InferredType::Int), InferredType::Int) => InferredType::Int,
+            _ => InferredType::Float,
+        });
+    }
+
+    if let (Some(_), Some((row, value))) = (candidate, big) {
+        if big_ints == BigIntPolicy::Error {
+            return Err(format!("Column '{}' has the integer '{}', which does not fit in 64 bits", column, value));
+        }
+        eprintln!("Warning: column '{}' kept as text, '{}' does not fit in a 64-bit integer", column, value);
+        let reason = format!("'{}' at data row {} does not fit in a 64-bit integer", value, row);
+        return Ok(Inference { ty: InferredType::Text, sampled, reason });
+    }
+    match (candidate, lossy) {
+        (None, _) => Ok(Inference { ty: InferredType::Text, sampled, reason: "no values".to_string() }),
+        (Some(_), Some((_, value))) if strict => Err(format!(
+            "Column '{}' looks numeric but '{}' would change when parsed as a number",
+            column, value
+        )),
+        (Some(_), Some((row, value))) => {
+            eprintln!(
+                "Warning: column '{}' kept as text, '{}' would change when parsed as a number",
+                column, value
+            );
+            let reason = format!("'{}' at data row {} would change when parsed as a number", value, row);
+            Ok(Inference { ty: InferredType::Text, sampled, reason })
+        }
+        (Some(InferredType::Int), None) => Ok(Inference { ty: InferredType::Int, sampled, reason: "every value is an integer".to_string() }),
+        (Some(ty), None) => {
+            let reason = match first_fraction {
+                Some((row, value)) => format!("every value is a number, '{}' at data row {} is not whole", value, row),
+                None => "every value is a number, some too large for a 64-bit integer".to_string(),
+            };
+            Ok(Inference { ty, sampled, reason })
+        }
+    }
+}
+
+
+/// Texts read as true or false, from `--bool-true` and `--bool-false`.
+#[derive(Clone, Debug, Default)]
+pub struct BoolTokens {
+    pub truthy: Vec<String>,
+    pub falsy: Vec<String>,
+    pub ignore_case: bool,
+    /// Whether a mostly boolean column also takes other values, as nulls
+    pub unmatched_null: bool,
+}
+
+impl BoolTokens {
+    pub fn parse(&self, value: &str) -> Option<bool> {
+        let value = value.trim();
+        let is = |token: &String| match self.ignore_case {
+            true => token.to_lowercase() == value.to_lowercase(),
+            false => token == value,
+        };
+        if self.truthy.iter().any(is) {
+            Some(true)
+        } else if self.falsy.iter().any(is) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a column is boolean: every non-empty value is a token or,
+    /// with `unmatched_null`, most of them are and the rest will be null.
+    pub fn is_bool_column<I, S>(&self, column: &str, values: I) -> bool
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let (mut matched, mut unmatched) = (0, 0);
+        for value in values {
+            let value = value.as_ref();
+            if value.trim().is_empty() {
+                continue;
+            }
+            match self.parse(value) {
+                Some(_) => matched += 1,
+                None => unmatched += 1,
+            }
+        }
+        if matched == 0 || (unmatched > 0 && !(self.unmatched_null && matched > unmatched)) {
+            return false;
+        }
+        if unmatched > 0 {
+            eprintln!("Warning: column '{}': {} values are not boolean tokens and were left empty", column, unmatched);
+        }
+        true
+    }
+}
+
+
+fn is_whole_number(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+
+/// Casts string columns to their inferred types, matching columns by position.
+///
+/// Empty strings and values that don't parse become null.
+pub fn apply_inferred_types(df: &mut DataFrame, types: &[Option<InferredType>], bools: &BoolTokens) -> PolarsResult<()> {
+    for (col_idx, ty) in types.iter().enumerate() {
+        let series = {
+            let column = &df.get_columns()[col_idx];
+            let Ok(values) = column.str() else { continue };
+            let name = column.name().clone();
+            match ty {
+                Some(InferredType::Int) => Int64Chunked::from_iter_options(
+                    name,
+                    values.into_iter().map(|v| v.and_then(|v| v.trim().parse::<i64>().ok())),
+                )
+                .into_series(),
+                Some(InferredType::Float) => Float64Chunked::from_iter_options(
+                    name,
+                    values.into_iter().map(|v| v.and_then(|v| v.trim().parse::<f64>().ok())),
+                )
+                .into_series(),
+                Some(InferredType::Bool) => BooleanChunked::from_iter_options(
+                    name,
+                    values.into_iter().map(|v| v.and_then(|v| bools.parse(v))),
+                )
+                .into_series(),
+                Some(InferredType::Text) | None => continue,
+            }
+        };
+        df.replace_column(col_idx, series)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inference_keeps_lossy_numbers_as_text() {
+        assert_eq!(infer_column_type("a", ["1", "", "20"], false, BigIntPolicy::String), Ok(InferredType::Int));
+        assert_eq!(infer_column_type("a", ["1", "2.5"], false, BigIntPolicy::String), Ok(InferredType::Float));
+        assert_eq!(infer_column_type("a", ["1.5", "1.10"], false, BigIntPolicy::String), Ok(InferredType::Text));
+        assert_eq!(infer_column_type("a", ["007"], false, BigIntPolicy::String), Ok(InferredType::Text));
+        assert!(infer_column_type("a", ["1.10"], true, BigIntPolicy::String).is_err());
+
+        let ids = ["1", "12345678901234567890123"];
+        assert_eq!(infer_column_type("id", ids, false, BigIntPolicy::String), Ok(InferredType::Text));
+        assert_eq!(infer_column_type("id", ids, false, BigIntPolicy::F64), Ok(InferredType::Float));
+        assert!(infer_column_type("id", ids, false, BigIntPolicy::Error).is_err());
+    }
+
+    #[test]
+    fn infers_boolean_columns_from_tokens() {
+        let mut bools = BoolTokens { truthy: vec!["Y".into(), "Да".into()], falsy: vec!["N".into(), "Нет".into()], ..Default::default() };
+        assert!(bools.is_bool_column("a", ["Y", "", "Нет"]));
+        assert!(!bools.is_bool_column("a", ["да", "N"]));
+        assert!(!bools.is_bool_column("a", ["Y", "N", "maybe"]));
+        assert!(!bools.is_bool_column("a", ["", ""]));
+        bools.ignore_case = true;
+        bools.unmatched_null = true;
+        assert_eq!(bools.parse(" да "), Some(true));
+        assert!(bools.is_bool_column("a", ["да", "N", "maybe"]));
+        assert!(!bools.is_bool_column("a", ["да", "maybe"]));
+    }
+}
@@ -0,0 +1,26 @@
+//! Reading Excel worksheets, shared by the `excel_reader` command line tool.
+//!
+//! Most users want the command line tool. The library exposes the lower
+//! level pieces for callers who need the raw cells, see [`read_raw`], or
+//! want to check which sheets exist first, see [`sheet_names`] and
+//! [`has_sheet`]. [`read_excel_with_meta`] reads a worksheet into a
+//! DataFrame and says which sheet, headers and types it used, and
+//! [`range_to_dataframe`] does the same for cells already read with calamine.
+//! Both convert the cells the way the command line does, with every setting
+//! of the command line in [`FrameOptions`].
+
+pub mod archive;
+pub mod decimal;
+pub mod delimited;
+pub mod display;
+pub mod error;
+pub mod formats;
+pub mod frame;
+pub mod infer;
+pub mod notes;
+pub mod pivot_cache;
+pub mod sheet;
+pub mod workbook;
+
+pub use frame::{FrameOptions, ReadMeta, range_to_dataframe, read_excel_with_meta};
+pub use workbook::{has_sheet, read_raw, sheet_names};
@@ -1,33 +1,148 @@
-use clap::{Command, Arg};
-use calamine::{Data, Range, Reader, Xlsx, open_workbook};
+use clap::{Command, Arg, ArgAction, ArgGroup, ArgMatches};
+use clap_complete::Shell;
+use calamine::{Data, Reader, Xlsx, XlsxError};
 use polars::prelude::*;
-use rayon::prelude::*;
-use polars::error::PolarsError;
+use std::borrow::Cow;
 use std::error::Error;
+use std::ffi::OsString;
+use std::process::ExitCode;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 
+mod config;
+mod output;
+mod stream;
+mod transform;
 
-fn main() -> Result<(), Box<dyn Error>> {
+use excel_reader::{archive, delimited, error, formats, infer, pivot_cache, workbook};
+use excel_reader::decimal::{DecimalPolicy, parse_decimal_spec};
+use excel_reader::frame::{self, ErrorLog, FrameOptions, TypePlan, cell_text, convert_rows, plan_types, process_headers, sheet_to_dataframe, validate_headers};
+use excel_reader::sheet::{self, AutoHeader, HeaderCollapse, SheetData, SheetPackage};
+use error::ExcelReaderError;
+use formats::DateSystem;
+use infer::{BigIntPolicy, BoolTokens};
+use pivot_cache::PivotCache;
+use output::{ChunkedWriter, WriteOptions};
+use stream::{BatchBuilder, StreamSettings, StreamType};
+use transform::FillNa;
+use workbook::{FormulaMode, SheetMatch, UnicodeForm, find_sheet_name, open_excel};
+
+fn main() -> ExitCode {
     // Parse command line arguments
-    let matches = Command::new("Excel Reader")
-        .version("1.0")
-        .author("YehorI")
-        .about("Excel worksheet processor")
-        .arg(Arg::new("path")
-            .short('p')
-            .long("path")
-            .help("Path to the Excel file")
-            .required(true))
-        .arg(Arg::new("worksheet")
-            .short('w')
-            .long("worksheet")
-            .help("Name of the worksheet to process")
-            .required(false))
-        .arg(Arg::new("header")
-            .short('t')
-            .long("header")
-            .help("Header row number")
-            .required(false))
-        .get_matches();
+    let outcome = match parse_args().and_then(|matches| configure_process(&matches).map(|()| matches)) {
+        Ok(matches) => match matches.get_one::<u64>("timeout") {
+            Some(&seconds) => run_with_timeout(matches, seconds),
+            None => run(&matches).map_err(|err| failure(&*err)),
+        },
+        Err(err) => Err(failure(&*err)),
+    };
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err((message, code)) => {
+            eprintln!("Error: {}", message);
+            ExitCode::from(code)
+        }
+    }
+}
+
+
+/// Sets up the thread pools and Polars' table printing, before any other thread starts.
+///
+/// Polars reads these settings from the environment only, and changing the
+/// environment is only sound while the process has a single thread, so this
+/// runs ahead of rayon's pool and the `--timeout` worker.
+fn configure_process(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let threads = matches.get_one::<usize>("max-threads").copied();
+    if threads == Some(0) {
+        return Err("--max-threads must be greater than zero".into());
+    }
+    let mut env: Vec<(&str, String)> = Vec::new();
+    // Polars keeps its own pool, sized from the environment when first used
+    env.extend(threads.map(|threads| ("POLARS_MAX_THREADS", threads.to_string())));
+    if matches.contains_id("count-by") || matches.get_flag("cell-type-stats") {
+        // Show every group or column instead of the truncated preview
+        env.push(("POLARS_FMT_MAX_ROWS", "-1".to_string()));
+    }
+    match matches.get_one::<usize>("head-cols") {
+        Some(head_cols) => env.push(("POLARS_FMT_MAX_COLS", head_cols.to_string())),
+        None if matches.get_flag("cell-type-stats") => env.push(("POLARS_FMT_MAX_COLS", "-1".to_string())),
+        None => {}
+    }
+    for (key, value) in env {
+        // SAFETY: main calls this before spawning any thread, so nothing reads the environment concurrently
+        unsafe { std::env::set_var(key, value) };
+    }
+    if let Some(threads) = threads {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+    }
+    Ok(())
+}
+
+
+/// The message and exit code a run fails with.
+fn failure(err: &(dyn Error + 'static)) -> (String, u8) {
+    // Known failure classes get their own exit code, everything else is 1
+    let code = err
+        .downcast_ref::<ExcelReaderError>()
+        .map_or(1, ExcelReaderError::exit_code);
+    (err.to_string(), code)
+}
+
+
+/// Runs on a worker thread and gives up after `seconds`, for `--timeout`.
+///
+/// A hung parse can't be interrupted, so the worker is left behind and ends
+/// with the process. Output files it was writing may be incomplete.
+fn run_with_timeout(matches: ArgMatches, seconds: u64) -> Result<(), (String, u8)> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(run(&matches).map_err(|err| failure(&*err)));
+    });
+    match receiver.recv_timeout(Duration::from_secs(seconds)) {
+        Ok(outcome) => outcome,
+        Err(RecvTimeoutError::Timeout) => Err(failure(&ExcelReaderError::Timeout(seconds))),
+        Err(RecvTimeoutError::Disconnected) => Err(("Processing stopped unexpectedly".to_string(), 1)),
+    }
+}
+
+
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    if let Some(("completions", sub)) = matches.subcommand() {
+        let shell = *sub.get_one::<Shell>("shell").unwrap();
+        clap_complete::generate(shell, &mut build_cli(), env!("CARGO_PKG_NAME"), &mut std::io::stdout());
+        return Ok(());
+    }
+
+    if matches.get_flag("dump-config") {
+        print!("{}", config::dump_config(&build_cli(), matches)?);
+        return Ok(());
+    }
+
+    let pivot = matches
+        .get_one::<String>("pivot")
+        .map(|s| transform::parse_pivot_spec(s))
+        .transpose()?;
+    let melt = matches
+        .get_one::<String>("melt")
+        .map(|s| transform::parse_melt_spec(s))
+        .transpose()?;
+    let fill_na = matches
+        .get_one::<String>("fill-na")
+        .map(|s| transform::parse_fill_na(s))
+        .transpose()?;
+    let group_agg = matches
+        .get_one::<String>("group-agg")
+        .map(|s| transform::parse_group_agg_spec(s))
+        .transpose()?;
+    let date_format = matches.get_one::<String>("date-format").map(|s| s.as_str());
+    let round = matches
+        .get_one::<String>("round")
+        .map(|s| transform::parse_round_spec(s))
+        .transpose()?;
+    let count_by: Option<Vec<String>> = matches
+        .get_one::<String>("count-by")
+        .map(|s| s.split(',').map(|c| c.trim().to_string()).collect());
 
     // Extract values from matches
     let path = matches.get_one::<String>("path").unwrap();
@@ -44,187 +159,2240 @@ fn main() -> Result<(), Box<dyn Error>> {
             .collect::<Result<Vec<_>, _>>()
     })
     .transpose()?;
+    let decimal_specs = matches
+        .get_many::<String>("decimal-columns")
+        .unwrap_or_default()
+        .map(|s| parse_decimal_spec(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    let decimal_policy = match matches.get_one::<String>("decimal-overflow").map(|s| s.as_str()) {
+        Some("round") => DecimalPolicy::Round,
+        _ => DecimalPolicy::Error,
+    };
+
+    let errors_to = matches.get_one::<String>("errors-to").map(|s| s.as_str());
+    let atomic = matches.get_flag("atomic-output");
+    let write_error_report = |options: &FrameOptions| match (&options.errors, errors_to) {
+        (Some(log), Some(path)) => write_error_log(log, path, atomic),
+        _ => Ok(()),
+    };
+
+    let separator = match (delimited::separator_for(path), matches.get_one::<String>("separator")) {
+        (None, Some(_)) => return Err("--separator only applies to .csv and .tsv input".into()),
+        (Some(default), separator) => Some(separator.map(|s| parse_separator(s)).transpose()?.unwrap_or(default)),
+        (None, None) => None,
+    };
+
+    let options = FrameOptions {
+        // Header rows and the worksheet are passed per sheet
+        sheet: None,
+        header_rows: Vec::new(),
+        separator,
+        sheet_match: SheetMatch {
+            exact: matches.get_flag("exact-sheet"),
+            unicode: match matches.get_one::<String>("normalize-unicode").map(|s| s.as_str()) {
+                Some("nfc") => Some(UnicodeForm::Nfc),
+                Some("nfd") => Some(UnicodeForm::Nfd),
+                Some("nfkc") => Some(UnicodeForm::Nfkc),
+                Some("nfkd") => Some(UnicodeForm::Nfkd),
+                _ => None,
+            },
+        },
+        sheet_range: matches.get_one::<String>("sheet-range").map(|s| parse_sheet_range(s)).transpose()?,
+        exclude_sheets: matches
+            .get_one::<String>("exclude-sheets")
+            .map(|s| s.split(',').map(|name| name.to_string()).filter(|name| !name.trim().is_empty()).collect())
+            .unwrap_or_default(),
+        normalize_cells: matches.get_flag("normalize-cells"),
+        formatted_values: matches.get_one::<String>("formatted-values").map(|s| split_tokens(Some(s))),
+        max_cell_length: matches.get_one::<u64>("limit-cell-length").map(|&n| n as usize),
+        formulas: match matches.get_one::<String>("formulas").map(|s| s.as_str()) {
+            Some("text") => FormulaMode::Text,
+            _ => FormulaMode::Value,
+        },
+        strict_headers: matches.get_flag("strict-headers"),
+        keep_header_in_data: matches.get_flag("keep-header-in-data"),
+        header_gap_as_data: matches.get_one::<String>("header-gap").is_some_and(|s| s == "data"),
+        header_collapse: match matches.get_one::<String>("header-collapse").map(|s| s.as_str()) {
+            Some("first") => HeaderCollapse::First,
+            Some("last") => HeaderCollapse::Last,
+            Some("nested") => HeaderCollapse::Nested,
+            _ => HeaderCollapse::Join,
+        },
+        header_separator: matches.get_one::<String>("header-sep").cloned(),
+        unnamed_prefix: matches.get_one::<String>("empty-header-prefix").cloned(),
+        unnamed_by_letter: matches.get_flag("deterministic-unnamed"),
+        ascii_headers: matches.get_flag("coerce-headers-ascii"),
+        header_from_frozen: matches.get_flag("header-from-frozen"),
+        visible_only: matches.get_flag("visible-only"),
+        auto_header: match matches.get_one::<String>("auto-header").map(|s| s.as_str()) {
+            Some("type-change") => Some(AutoHeader::TypeChange),
+            Some(_) => Some(AutoHeader::Filled),
+            None => matches.get_flag("first-row-header-per-sheet").then_some(AutoHeader::Filled),
+        },
+        numeric_header_prefix: matches.get_one::<String>("prefix-numeric-headers").cloned(),
+        header_renames: matches
+            .get_many::<String>("rename-regex")
+            .unwrap_or_default()
+            .map(|s| sheet::parse_rename_regex(s))
+            .collect::<Result<Vec<_>, _>>()?,
+        header_map: matches
+            .get_one::<String>("rename-map")
+            .map(|path| sheet::parse_rename_map(path))
+            .transpose()?
+            .unwrap_or_default(),
+        rename_strict: matches.get_flag("rename-strict"),
+        currency_symbols: matches
+            .get_one::<String>("strip-currency-symbols")
+            .map(|s| s.split(',').map(|symbol| symbol.trim().to_string()).filter(|symbol| !symbol.is_empty()).collect())
+            .unwrap_or_default(),
+        thousands_separator: matches.get_one::<String>("strip-thousands").cloned(),
+        last_row: matches.get_one::<u32>("last-row").copied(),
+        cell_range: matches
+            .get_one::<String>("range")
+            .map(|s| workbook::parse_cells_ref(s).ok_or_else(|| format!("Invalid --range '{}', expected e.g. B3:E40 or A:C", s)))
+            .transpose()?,
+        auto_anchor: matches.get_one::<String>("anchor").is_some_and(|s| s == "auto"),
+        coalesce_headers: matches.get_flag("coalesce-headers"),
+        header_fill_forward: matches.get_flag("header-fill-forward"),
+        ffill_header_rows: matches
+            .get_one::<String>("ffill-header-rows")
+            .map(|s| {
+                s.split(',')
+                    .map(|v| v.trim().parse::<usize>().map_err(|_| format!("Invalid --ffill-header-rows '{}', expected e.g. 0,1", s)))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default(),
+        units_row: matches.get_one::<usize>("units-row").copied(),
+        use_formats: matches.get_flag("use-formats"),
+        date_system: match matches.get_one::<String>("date-system").map(|s| s.as_str()) {
+            Some("1900") => DateSystem::Excel1900,
+            Some("1904") => DateSystem::Excel1904,
+            _ => DateSystem::Auto,
+        },
+        infer_types: matches.get_flag("infer-types"),
+        empty_as_null: matches.get_flag("empty-string-as-null"),
+        strict_numeric: matches.get_flag("strict-numeric"),
+        schema_from_first_row: matches.get_flag("schema-from-first-row"),
+        mixed_dates_threshold: matches.get_one::<f64>("abort-on-mixed-dates").copied(),
+        big_ints: match matches.get_one::<String>("big-int-policy").map(|s| s.as_str()) {
+            Some("f64") => BigIntPolicy::F64,
+            Some("error") => BigIntPolicy::Error,
+            _ => BigIntPolicy::String,
+        },
+        bools: BoolTokens {
+            truthy: split_tokens(matches.get_one::<String>("bool-true")),
+            falsy: split_tokens(matches.get_one::<String>("bool-false")),
+            ignore_case: matches.get_flag("bool-ignore-case"),
+            unmatched_null: matches.get_one::<String>("bool-unmatched").is_some_and(|s| s == "null"),
+        },
+        expected_headers: matches
+            .get_one::<String>("validate-schema")
+            .map(|s| parse_schema_list(s))
+            .transpose()?,
+        decimal_columns: decimal_specs,
+        decimal_policy,
+        explode: matches
+            .get_many::<String>("explode-delimited")
+            .unwrap_or_default()
+            .map(|s| frame::parse_explode_spec(s))
+            .collect::<Result<Vec<_>, _>>()?,
+        row_hash: matches.get_one::<String>("row-hash").cloned(),
+        index_column: matches.get_one::<String>("index-col").cloned(),
+        index_first: matches.get_flag("index-first"),
+        errors: errors_to.map(|_| ErrorLog::default()),
+    };
+
+    let template = matches.get_one::<String>("output-template");
+    let output_sheet = matches.get_one::<String>("output-sheet").map(|s| s.as_str());
+    let write_options = WriteOptions {
+        sheet_name: output_sheet,
+        json_pretty: matches.get_flag("json-pretty"),
+        json_nested: options.header_collapse == HeaderCollapse::Nested,
+        markdown_max_rows: matches.get_one::<usize>("markdown-max-rows").copied(),
+        markdown_max_cols: matches.get_one::<usize>("markdown-max-cols").copied(),
+        atomic,
+        schema_sidecar: matches.get_flag("write-schema-sidecar"),
+    };
+    let chunksize = matches.get_one::<usize>("chunksize").copied();
+    if chunksize == Some(0) {
+        return Err("--chunksize must be greater than zero".into());
+    }
+
+    let is_glob = archive::is_glob(path);
+    if archive::is_archive(path) || is_glob {
+        // Archive entries and glob matches are read one worksheet each, sheet-level modes need a single workbook
+        let input = if is_glob { "a glob pattern" } else { "a .zip archive" };
+        let unsupported = ["table", "named-range", "pivot-cache", "chunksize", "extract-notes", "output-template", "kv-mode"];
+        if let Some(arg) = unsupported.iter().find(|id| matches.contains_id(id)) {
+            return Err(format!("--{} cannot be used with {}, read the workbooks one at a time", arg, input).into());
+        }
+        if matches.get_flag("all-sheets") {
+            return Err(format!("--all-sheets cannot be used with {}, pick one sheet with --worksheet", input).into());
+        }
+        if ["explain", "dump-merges", "type-report", "cell-type-stats"].iter().any(|id| matches.get_flag(id)) || matches.contains_id("headers-only") {
+            return Err(format!("--explain, --dump-merges, --type-report, --cell-type-stats and --headers-only cannot be used with {}, use one of its workbooks instead", input).into());
+        }
+    }
+    if archive::is_archive(path) {
+        // Archive entries are read in memory, these options read parts of the workbook file itself
+        let package_options = ["header-from-frozen", "visible-only"].into_iter().filter(|id| matches.get_flag(id));
+        if let Some(arg) = package_options.chain(["formatted-values"].into_iter().filter(|id| matches.contains_id(id))).next() {
+            return Err(format!("--{} cannot be used with a .zip archive, unpack it first", arg).into());
+        }
+    }
+    if options.separator.is_some() {
+        // A delimited file is a single sheet of text cells
+        let unsupported = ["worksheet", "table", "named-range", "pivot-cache", "kv-mode", "extract-notes", "formatted-values"];
+        if let Some(arg) = unsupported.iter().find(|id| matches.contains_id(id)) {
+            return Err(format!("--{} cannot be used with .csv or .tsv input", arg).into());
+        }
+        let flags = ["all-sheets", "stream", "dump-merges", "header-from-frozen", "use-formats", "visible-only"];
+        if let Some(flag) = flags.iter().find(|id| matches.get_flag(id)) {
+            return Err(format!("--{} cannot be used with .csv or .tsv input", flag).into());
+        }
+    }
+    if !archive::is_archive(path) && matches.contains_id("inner-glob") {
+        return Err("--inner-glob only applies to .zip archive input".into());
+    }
+
+    let dry = matches.get_flag("dry-output");
+    // Paths written as given, next to the data outputs
+    let side_files: Vec<String> = ["extract-notes", "profile-out", "errors-to"]
+        .iter()
+        .filter_map(|id| matches.get_one::<String>(id).cloned())
+        .collect();
 
     // Use the arguments from CLI
-    let df = process_excel_worksheet(path, worksheet, header_rows)?;
-    println!("{}", df.head(Some(10)));
-    Ok(())
+    if let (Some(template), true, true) = (template, matches.get_flag("all-sheets"), dry) {
+        let mut excel = open_excel(path)?;
+        let mut targets = Vec::new();
+        for name in all_sheet_names(excel.sheet_names(), &options)? {
+            // Empty sheets are skipped and get no file
+            if workbook::worksheet_range(&mut excel, Some(&name), options.sheet_match, options.formulas)?.is_empty() {
+                continue;
+            }
+            let target = output::expand_template(template, path, &name)?;
+            if targets.contains(&target) {
+                return Err(format!("--output-template gives the same path '{}' for several sheets", target).into());
+            }
+            targets.push(target);
+        }
+        targets.iter().chain(&side_files).for_each(|target| println!("{}", target));
+        return Ok(());
+    }
+    if let (Some(template), true) = (template, matches.get_flag("all-sheets")) {
+        // One file per sheet, the template must tell them apart
+        let mut targets = Vec::new();
+        for (name, mut df) in read_all_sheets(path, header_rows, &options)? {
+            if let Some(format) = date_format {
+                df = transform::format_dates(&df, format)?;
+            }
+            let target = output::expand_template(template, path, &name)?;
+            if targets.contains(&target) {
+                return Err(format!("--output-template gives the same path '{}' for several sheets", target).into());
+            }
+            let sheet_name = Some(output_sheet.unwrap_or(&name));
+            output::write_dataframe(&mut df, &target, &WriteOptions { sheet_name, ..write_options })?;
+            targets.push(target);
+        }
+        return Ok(());
+    }
+    let table = matches.get_one::<String>("table").map(|s| s.as_str());
+    let kv_layout = match matches.get_one::<String>("kv-mode").map(|s| s.as_str()) {
+        Some("rows") => Some(KeyValueLayout::Rows),
+        Some("cols") => Some(KeyValueLayout::Cols),
+        _ => None,
+    };
+    let named_range = matches.get_one::<String>("named-range").map(|s| s.as_str());
+    let pivot_cache = matches.get_one::<String>("pivot-cache").map(|s| s.as_str());
+    let selected_sheet = || match named_range {
+        Some(name) => Ok(workbook::named_range(&open_excel(path)?, name)?.sheet),
+        None => sheet::resolve_sheet_name(path, worksheet, table, &options),
+    };
+    // The same DataFrame can go to several files, each in the format of its extension
+    let outputs: Vec<String> = match template {
+        Some(template) => vec![output::expand_template(template, path, &selected_sheet()?)?],
+        None => matches.get_many::<String>("output").unwrap_or_default().cloned().collect(),
+    };
+    // A dataset's files depend on the data, everything else is known already
+    if dry && !matches.contains_id("output-dataset") {
+        outputs.iter().chain(&side_files).for_each(|target| println!("{}", target));
+        return Ok(());
+    }
+    if matches.get_flag("dump-merges") {
+        let sheet = selected_sheet()?;
+        for area in workbook::merged_cells(&mut open_excel(path)?, &sheet)? {
+            println!("{}", area.cells_ref());
+        }
+        return Ok(());
+    }
+    // Rows and headers of the table, named range, pivot cache or worksheet the run reads
+    let load_selected = || match (table, named_range, pivot_cache) {
+        (Some(table), _, _) => load_table(path, table, &options),
+        (None, Some(name), _) => load_named_range(path, name, header_rows.clone(), &options),
+        (None, None, Some(name)) => load_pivot_cache(path, name, &options),
+        (None, None, None) => load_sheet(path, worksheet, header_rows.clone(), &options),
+    };
+    if let Some(format) = matches.get_one::<String>("headers-only") {
+        let names = process_headers(load_selected()?.headers);
+        match format.as_str() {
+            "json" => println!("{}", serde_json::to_string(&names)?),
+            _ => names.iter().for_each(|name| println!("{}", name)),
+        }
+        return Ok(());
+    }
+    if matches.get_flag("type-report") {
+        print!("{}", type_report(&load_selected()?, &options)?);
+        return Ok(());
+    }
+    if matches.get_flag("cell-type-stats") {
+        println!("{}", cell_type_stats(&load_selected()?)?);
+        return Ok(());
+    }
+    if matches.get_flag("explain") {
+        let source = match (table, named_range) {
+            (Some(table), _) => format!("table '{}' on sheet '{}'", table, selected_sheet()?),
+            (None, Some(name)) => format!("named range '{}' on sheet '{}'", name, selected_sheet()?),
+            (None, None) if options.separator.is_some() => format!("delimited file '{}'", path),
+            (None, None) => format!("worksheet '{}'", selected_sheet()?),
+        };
+        let sheet = load_selected()?;
+        let header = match table {
+            Some(_) => "the table's own header row".to_string(),
+            None => format!("{:?}", header_rows.unwrap_or(vec![0])),
+        };
+        let mut steps: Vec<String> = ["melt", "pivot", "fill-na", "group-agg", "date-format", "count-by"]
+            .iter()
+            .filter_map(|id| matches.get_one::<String>(id).map(|value| format!("--{} {}", id, value)))
+            .collect();
+        if let Some(chunksize) = chunksize {
+            steps.push(format!("convert and write {} rows at a time", chunksize));
+        }
+        print!("{}", explain_plan(path, &source, &header, &sheet, &options, &steps, &outputs)?);
+        return Ok(());
+    }
+    if let Some(notes_path) = matches.get_one::<String>("extract-notes").filter(|_| !dry) {
+        let sheets = if matches.get_flag("all-sheets") {
+            workbook::sheet_names(path)?
+        } else {
+            vec![selected_sheet()?]
+        };
+        let notes_options = WriteOptions { sheet_name: Some("Notes"), json_nested: false, ..write_options };
+        output::write_dataframe(&mut notes_dataframe(path, &sheets)?, notes_path, &notes_options)?;
+    }
+
+    // Batched writers for every output, created once the first batch gives the schema
+    let create_writers = |schema: &Schema| {
+        outputs
+            .iter()
+            .map(|output| ChunkedWriter::create(output, schema, &write_options))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    if let Some(batch_rows) = chunksize.filter(|_| matches.get_flag("stream")) {
+        let sample_rows = matches.get_one::<usize>("stream-sample").copied().unwrap_or(1000);
+        if sample_rows == 0 {
+            return Err("--stream-sample must be greater than zero".into());
+        }
+        let header_row = match header_rows.as_deref() {
+            None => 0,
+            Some(&[row]) => row,
+            Some(_) => return Err("--stream reads a single --header row".into()),
+        };
+        let sheet_name = sheet::resolve_sheet_name(path, worksheet, None, &options)?;
+        let finish_batch = |mut df: DataFrame| -> Result<DataFrame, Box<dyn Error>> {
+            if let Some(fill) = &fill_na {
+                df = transform::fill_na(&df, fill)?;
+            }
+            if let Some(format) = date_format {
+                df = transform::format_dates(&df, format)?;
+            }
+            Ok(df)
+        };
+        let settings = StreamSettings { batch_rows, sample_rows };
+        stream_worksheet(path, &sheet_name, header_row, create_writers, &options, settings, finish_batch)?;
+        return write_error_report(&options);
+    }
+    if let Some(chunksize) = chunksize {
+        let fill_na = fill_na.as_ref();
+        match (table, named_range, pivot_cache) {
+            (None, None, None) => {
+                let sheets = load_sheet_chunks(path, worksheet, header_rows, &options, chunksize)?;
+                write_in_chunks(create_writers, sheets, &options, fill_na, date_format, chunksize)?;
+            }
+            _ => write_in_chunks(create_writers, std::iter::once(load_selected()), &options, fill_na, date_format, chunksize)?,
+        }
+        return write_error_report(&options);
+    }
+
+    let mut df = if is_glob {
+        let files = archive::glob_workbooks(path)?;
+        let files: Vec<(String, &str)> = files.iter().map(|file| (file.clone(), file.as_str())).collect();
+        process_files(&files, worksheet, header_rows, &options, matches.get_flag("union"))?
+    } else if archive::is_archive(path) {
+        let inner_glob = matches.get_one::<String>("inner-glob").map(|s| s.as_str());
+        process_archive(path, inner_glob, worksheet, header_rows, &options, matches.get_flag("union"))?
+    } else if matches.get_flag("all-sheets") {
+        process_all_sheets(path, header_rows, &options, matches.get_flag("union"))?
+    } else if let Some(table) = table {
+        sheet_to_dataframe(&load_table(path, table, &options)?, &options)?
+    } else if let Some(name) = named_range {
+        sheet_to_dataframe(&load_named_range(path, name, header_rows, &options)?, &options)?
+    } else if let Some(name) = pivot_cache {
+        sheet_to_dataframe(&load_pivot_cache(path, name, &options)?, &options)?
+    } else if let Some(layout) = kv_layout {
+        load_key_values(path, worksheet, layout, &options)?
+    } else {
+        process_excel_worksheet(path, worksheet, header_rows, &options)?
+    };
+    let selection = matches
+        .get_one::<String>("columns")
+        .map(|columns| {
+            let sheet_order = matches.get_one::<String>("columns-order").is_some_and(|s| s == "sheet");
+            transform::selected_columns(&df, &split_tokens(Some(columns)), sheet_order)
+        })
+        .transpose()?;
+    if matches.get_flag("explain-lazy") {
+        // The sheet is already in memory, so the plan starts from a scan of that DataFrame
+        let mut lf = df.lazy();
+        if let Some(columns) = &selection {
+            lf = lf.select(columns.iter().map(|c| col(c.as_str())).collect::<Vec<_>>());
+        }
+        if let Some(group) = &group_agg {
+            lf = transform::group_agg_lazy(lf, group);
+        }
+        println!("{}", lf.explain(true)?);
+        return Ok(());
+    }
+    if let Some(columns) = &selection {
+        df = df.select(columns)?;
+    }
+    if let Some(melt) = &melt {
+        df = transform::melt(&df, melt)?;
+    }
+    if let Some(pivot) = &pivot {
+        df = transform::pivot(&df, pivot)?;
+    }
+    if let Some(fill) = &fill_na {
+        df = transform::fill_na(&df, fill)?;
+    }
+    if let Some(group) = &group_agg {
+        df = transform::group_agg(&df, group)?;
+    }
+    if let Some(format) = date_format {
+        df = transform::format_dates(&df, format)?;
+    }
+    if let Some(round) = &round {
+        df = transform::round_columns(&df, round)?;
+    }
+    if matches.get_flag("drop-duplicate-rows") || matches.contains_id("drop-duplicate-rows-on") {
+        let subset = matches.get_one::<String>("drop-duplicate-rows-on").map(|s| split_tokens(Some(s)));
+        let keep_last = matches.get_one::<String>("keep").is_some_and(|s| s == "last");
+        df = transform::drop_duplicate_rows(&df, subset.as_deref(), keep_last)?;
+    }
+    if let Some(profile_path) = matches.get_one::<String>("profile-out").filter(|_| !dry) {
+        output::write_profile(&df, profile_path, write_options.atomic)?;
+    }
+    if let Some(columns) = count_by {
+        println!("{}", transform::count_by(&df, &columns)?);
+        return write_error_report(&options);
+    }
+    if let Some(columns) = matches.get_one::<String>("count-distinct") {
+        let columns = split_tokens(Some(columns));
+        let count = transform::count_distinct(&df, &columns)?;
+        println!("Columns:  {}", columns.join(", "));
+        println!("Rows:     {}", count.rows);
+        println!("Non-null: {}", count.non_null);
+        println!("Distinct: {}", count.distinct);
+        let problems: Vec<String> = [(count.rows - count.non_null, "have a null key"), (count.non_null - count.distinct, "repeat a key")]
+            .iter()
+            .filter(|(rows, _)| *rows > 0)
+            .map(|(rows, problem)| format!("{} rows {}", rows, problem))
+            .collect();
+        match count.is_unique() {
+            true => println!("Unique:   yes"),
+            false => println!("Unique:   no, {}", problems.join(", ")),
+        }
+        return write_error_report(&options);
+    }
+    check_shape(&df, matches)?;
+    if let Some(columns) = matches.get_one::<String>("validate-unique") {
+        check_unique(&df, &split_tokens(Some(columns)))?;
+    }
+    let dataset = matches.get_one::<String>("output-dataset");
+    if let (Some(dir), true) = (dataset, dry) {
+        let partition_by = split_tokens(matches.get_one::<String>("partition-by"));
+        let files = output::partition_files(&df, dir, &partition_by)?;
+        outputs.iter().chain(&files).chain(&side_files).for_each(|target| println!("{}", target));
+        return Ok(());
+    }
+    if outputs.is_empty() && dataset.is_none() {
+        println!("{}", df.head(Some(10)));
+    }
+    for output in &outputs {
+        output::write_dataframe(&mut df, output, &write_options)?;
+    }
+    if let Some(dir) = dataset {
+        let partition_by = split_tokens(matches.get_one::<String>("partition-by"));
+        output::write_partitioned(&mut df, dir, &partition_by, write_options.atomic)?;
+    }
+    write_error_report(&options)
 }
 
 
-fn process_excel_worksheet(
+/// Checks the final DataFrame against `--assert-rows`, `--assert-cols`, `--min-rows` and `--max-rows-assert`.
+fn check_shape(df: &DataFrame, matches: &ArgMatches) -> Result<(), ExcelReaderError> {
+    let bound = |id: &str| matches.get_one::<usize>(id).copied();
+    let (rows, cols) = df.shape();
+    let mut problems = Vec::new();
+    if let Some(expected) = bound("assert-rows").filter(|&n| n != rows) {
+        problems.push(format!("expected {} rows, got {}", expected, rows));
+    }
+    if let Some(expected) = bound("assert-cols").filter(|&n| n != cols) {
+        problems.push(format!("expected {} columns, got {}", expected, cols));
+    }
+    if let Some(min) = bound("min-rows").filter(|&n| rows < n) {
+        problems.push(format!("expected at least {} rows, got {}", min, rows));
+    }
+    if let Some(max) = bound("max-rows-assert").filter(|&n| rows > n) {
+        problems.push(format!("expected at most {} rows, got {}", max, rows));
+    }
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(ExcelReaderError::ShapeMismatch(problems.join("; "))),
+    }
+}
+
+
+/// Fails `--validate-unique` when non-null values of `columns` repeat, after printing them with their counts.
+fn check_unique(df: &DataFrame, columns: &[String]) -> Result<(), Box<dyn Error>> {
+    let duplicates = transform::duplicate_keys(df, columns)?;
+    if duplicates.height() == 0 {
+        return Ok(());
+    }
+    eprintln!("{}", duplicates);
+    let rows: u64 = duplicates.get_columns()[columns.len()].as_materialized_series().sum()?;
+    let problem = format!("{} values of {} repeat, in {} rows", duplicates.height(), columns.join(", "), rows);
+    Err(ExcelReaderError::DuplicateKeys(problem).into())
+}
+
+
+/// Describes what a run would read and produce, for `--explain`.
+///
+/// Column types come from the same plan the conversion uses, applied to
+/// zero rows, so no cell is converted.
+fn explain_plan(
     path: &str,
-    worksheet_name: Option<&str>,
-    header_rows: Option<Vec<usize>>, // <-- modified type
-) -> Result<DataFrame, Box<dyn Error>> {
-    let range = get_worksheet_range(path, worksheet_name)?;
-    let header_rows = header_rows.unwrap_or(vec![0]);
+    source: &str,
+    header: &str,
+    sheet: &SheetData,
+    options: &FrameOptions,
+    steps: &[String],
+    outputs: &[String],
+) -> Result<String, Box<dyn Error>> {
+    validate_headers(&sheet.headers, options)?;
+    let plan = plan_types(sheet, options)?;
+    let empty = convert_rows(&sheet.headers, &[], 0, &plan, options)?;
 
-    let rows: Vec<Vec<Data>> = range.rows().map(|row| row.to_vec()).collect();
-    // Check header indices are in bounds
-    for &idx in &header_rows {
-        if idx >= rows.len() {
-            return Err("One of header row indices is out of bounds".into());
-        }
+    let mut text = format!("Input:   {}\nSource:  {}\nHeader:  {}\nRows:    {} data rows\nColumns:\n", path, source, header, sheet.data_rows().len());
+    for (name, dtype) in empty.schema().iter() {
+        text.push_str(&format!("  {}: {}\n", name, dtype));
     }
+    let mut reading: Vec<String> = options
+        .explode
+        .iter()
+        .map(|spec| format!("explode '{}' on '{}'", spec.column, spec.separator))
+        .collect();
+    if options.coalesce_headers {
+        reading.insert(0, "coalesce columns with the same header".to_string());
+    }
+    text.push_str("Steps:\n");
+    if reading.is_empty() && steps.is_empty() {
+        text.push_str("  none\n");
+    }
+    for step in reading.iter().chain(steps) {
+        text.push_str(&format!("  {}\n", step));
+    }
+    match outputs {
+        [] => text.push_str("Output:  preview of the first 10 rows\n"),
+        outputs => text.push_str(&format!("Output:  {}\n", outputs.join(", "))),
+    }
+    Ok(text)
+}
 
-    // Collect header rows
-    let header_cells: Vec<&[Data]> = header_rows.iter().map(|&i| &rows[i][..]).collect();
-    // Collapse headers
-    let headers = collapse_multi_headers(&header_cells)?;
 
-    // Data starts after the last header row
-    let data_start = header_rows.iter().max().map(|x| x+1).unwrap_or(1);
-    let data_rows = &rows[data_start..];
-    let data = extract_data(data_rows, headers.len());
-    let df = create_dataframe(headers, data)?;
-    Ok(df)
+/// One row per cell note of `sheets`, with the 1-based row and column of the cell.
+fn notes_dataframe(path: &str, sheets: &[String]) -> Result<DataFrame, Box<dyn Error>> {
+    let mut columns: [Vec<String>; 4] = Default::default();
+    let (mut rows, mut cols) = (Vec::new(), Vec::new());
+    for sheet in sheets {
+        for note in excel_reader::notes::read_notes(path, sheet)? {
+            columns[0].push(sheet.clone());
+            columns[1].push(note.cell);
+            columns[2].push(note.author);
+            columns[3].push(note.text);
+            rows.push(note.row);
+            cols.push(note.col);
+        }
+    }
+    let [sheet, cell, author, note] = columns;
+    Ok(DataFrame::new(vec![
+        Column::new("sheet".into(), sheet),
+        Column::new("cell".into(), cell),
+        Column::new("row".into(), rows),
+        Column::new("column".into(), cols),
+        Column::new("author".into(), author),
+        Column::new("note".into(), note),
+    ])?)
 }
 
 
-fn collapse_multi_headers(header_cells: &Vec<&[Data]>) -> Result<Vec<String>, Box<dyn Error>> {
-    if header_cells.is_empty() {
-        return Err("Empty header cells".into());
+/// Parses a `--sheet-range` such as "2:6", "2:" or ":6" into a start and an optional end.
+fn parse_sheet_range(spec: &str) -> Result<(usize, Option<usize>), Box<dyn Error>> {
+    let invalid = || format!("Invalid --sheet-range '{}', expected START:END with 0-based positions, e.g. 2:6 or 2:", spec);
+    let (start, end) = spec.split_once(':').ok_or_else(invalid)?;
+    let position = |text: &str| text.trim().parse::<usize>().map_err(|_| invalid());
+    let start = if start.trim().is_empty() { 0 } else { position(start)? };
+    let end = if end.trim().is_empty() { None } else { Some(position(end)?) };
+    if end.is_some_and(|end| end <= start) {
+        return Err(format!("--sheet-range '{}' selects no sheets, the end must be past the start", spec).into());
     }
-    let cols = header_cells[0].len();
-    let mut collapsed = Vec::with_capacity(cols);
-    for col_idx in 0..cols {
-        let parts: Vec<String> = header_cells.iter()
-            .map(|row| row.get(col_idx).map(|d| d.to_string()).unwrap_or_default())
-            .filter(|part| !part.starts_with("Unnamed") && !part.trim().is_empty())
-            .collect();
+    Ok((start, end))
+}
 
-        collapsed.push(if parts.is_empty() {
-            format!("Unnamed_{}", col_idx)
-        } else {
-            parts.join(" ")
-        });
+
+/// Parses a share between 0 and 1, e.g. "0.05".
+fn parse_share(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(share) if (0.0..=1.0).contains(&share) => Ok(share),
+        _ => Err(format!("'{}' is not a number between 0 and 1", value)),
     }
-    Ok(collapsed)
 }
 
 
-fn get_worksheet_range(path: &str, worksheet_name: Option<&str>) -> Result<Range<Data>, Box<dyn Error>> {
-    let mut workbook: Xlsx<_> = open_workbook(path)?;
+/// Splits a comma-separated list of tokens, dropping empty ones.
+fn split_tokens(list: Option<&String>) -> Vec<String> {
+    list.map(|s| s.split(',').map(|token| token.trim().to_string()).filter(|token| !token.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+
+/// Parses "col1,col2,col3", or reads the names from a file given as "@path".
+///
+/// A file lists one name per line, or comma-separated names.
+fn parse_schema_list(spec: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let text = match spec.strip_prefix('@') {
+        Some(file) => std::fs::read_to_string(file).map_err(|e| format!("Cannot read schema file '{}': {}", file, e))?,
+        None => spec.to_string(),
+    };
+    let names: Vec<String> = text
+        .lines()
+        .flat_map(|line| line.split(','))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if names.is_empty() {
+        return Err("--validate-schema needs at least one column name".into());
+    }
+    Ok(names)
+}
+
 
-    let range = match worksheet_name {
-        Some(name) => workbook.worksheet_range(name)?,
-        None => {
-            // Get the first worksheet
-            let sheets = workbook.worksheets();
-            if sheets.is_empty() {
-                return Err("No worksheets found in the workbook".into());
+/// Converts `chunksize` data rows at a time and writes them to every output.
+///
+/// `sheets` are read one after the other, a worksheet a chunk at a time. Column
+/// types come from the first of them so every chunk gets the same schema.
+fn write_in_chunks(
+    create: impl Fn(&Schema) -> Result<Vec<ChunkedWriter>, Box<dyn Error>>,
+    sheets: impl Iterator<Item = Result<SheetData, Box<dyn Error>>>,
+    options: &FrameOptions,
+    fill_na: Option<&FillNa>,
+    date_format: Option<&str>,
+    chunksize: usize,
+) -> Result<(), Box<dyn Error>> {
+    let mut first: Option<(Vec<String>, TypePlan)> = None;
+    let mut writers: Option<Vec<ChunkedWriter>> = None;
+    let mut converted = 0;
+    for sheet in sheets {
+        let sheet = sheet?;
+        let (_, plan) = match &first {
+            Some(first) => first,
+            None => {
+                validate_headers(&sheet.headers, options)?;
+                let plan = TypePlan { first_chunk: true, ..plan_types(&sheet, options)? };
+                first.insert((sheet.headers.clone(), plan))
             }
+        };
+        for chunk in sheet.data_rows().chunks(chunksize) {
+            let mut df = convert_rows(&sheet.headers, chunk, converted, plan, options)?;
+            converted += chunk.len();
+            if let Some(fill) = fill_na {
+                df = transform::fill_na(&df, fill)?;
+            }
+            if let Some(format) = date_format {
+                df = transform::format_dates(&df, format)?;
+            }
+            let writers = match writers.as_mut() {
+                Some(writers) => writers,
+                None => writers.insert(create(df.schema())?),
+            };
+            for writer in writers.iter_mut() {
+                writer.write_chunk(&df)?;
+            }
+        }
+    }
+    let writers = match (writers, first) {
+        (Some(writers), _) => writers,
+        (None, None) => return Ok(()),
+        (None, Some((headers, plan))) => {
+            // No data rows: still produce files with the header/schema
+            let mut df = convert_rows(&headers, &[], 0, &plan, options)?;
+            if let Some(format) = date_format {
+                df = transform::format_dates(&df, format)?;
+            }
+            create(df.schema())?
+        }
+    };
+    writers.into_iter().try_for_each(ChunkedWriter::finish)
+}
 
-            // Clone the range from the first worksheet
-            // sheets[0] contains a tuple of (name, range)
-            sheets[0].1.clone()
+
+/// Converts a worksheet to the outputs a batch at a time, reading it cell by cell.
+///
+/// Only the sample rows and the current batch are held in memory, so column
+/// types come from the first `sample_rows` data rows. Later cells that don't
+/// fit their column's type are written as null, with a warning.
+fn stream_worksheet(
+    path: &str,
+    sheet_name: &str,
+    header_row: usize,
+    create_writers: impl Fn(&Schema) -> Result<Vec<ChunkedWriter>, Box<dyn Error>>,
+    options: &FrameOptions,
+    settings: StreamSettings,
+    finish_batch: impl Fn(DataFrame) -> Result<DataFrame, Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut workbook = open_excel(path)?;
+    let mut rows = stream::sheet_rows(&mut workbook, sheet_name)?;
+    let first_filled = |row: &Vec<Data>| row.iter().position(|cell| *cell != Data::Empty);
+    // Rows above the header, like a title, still widen the columns as they do a range
+    let mut first_col = usize::MAX;
+    let mut read = 0;
+    let header = loop {
+        let Some(row) = rows.next_row()? else {
+            let first_row = rows.first_row().map_or(1, |row| row + 1);
+            return Err(ExcelReaderError::HeaderOutOfBounds { index: header_row, rows: read, first_row }.into());
+        };
+        if read == header_row {
+            break row;
         }
+        first_col = first_col.min(first_filled(&row).unwrap_or(usize::MAX));
+        read += 1;
     };
+    let mut sample = Vec::new();
+    while sample.len() < settings.sample_rows && let Some(row) = rows.next_row()? {
+        sample.push(row);
+    }
 
-    Ok(range)
+    // Columns span the header and sample cells, as a range over them would
+    let first_col = std::iter::once(&header)
+        .chain(&sample)
+        .filter_map(first_filled)
+        .fold(first_col, usize::min);
+    let first_col = if first_col == usize::MAX { 0 } else { first_col };
+    let width = std::iter::once(&header).chain(&sample).map(Vec::len).max().unwrap_or(0).saturating_sub(first_col);
+    let mut header_cells = header.get(first_col..).unwrap_or_default().to_vec();
+    header_cells.resize(width, Data::Empty);
+    let unnamed_col = options.unnamed_by_letter.then_some(first_col as u32);
+    let mut headers = sheet::collapse_multi_headers(&vec![&header_cells[..]], options, unnamed_col)?;
+    sheet::normalize_unicode(&mut headers, &mut [], options);
+    sheet::coerce_headers_ascii(&mut headers, options);
+    sheet::prefix_numeric_headers(&mut headers, options);
+    sheet::rename_headers(&mut headers, options)?;
+    validate_headers(&headers, options)?;
+    let types: Vec<StreamType> = (first_col..first_col + width)
+        .map(|col| StreamType::infer(sample.iter().filter_map(|row| row.get(col))))
+        .collect();
+
+    let mut batch = BatchBuilder::new(process_headers(headers), &types, first_col);
+    let mut writers: Vec<ChunkedWriter> = Vec::new();
+    let mut write_batch = |batch: &mut BatchBuilder| -> Result<(), Box<dyn Error>> {
+        let df = finish_batch(batch.take()?)?;
+        if writers.is_empty() {
+            writers = create_writers(df.schema())?;
+        }
+        writers.iter_mut().try_for_each(|writer| writer.write_chunk(&df))
+    };
+    let mut written = false;
+    for row in sample.into_iter().map(Ok).chain(std::iter::from_fn(|| rows.next_row().transpose())) {
+        batch.push_row(&row?);
+        if batch.len() == settings.batch_rows {
+            write_batch(&mut batch)?;
+            written = true;
+        }
+    }
+    // The last, partial batch, or with no data rows the header/schema alone
+    if batch.len() > 0 || !written {
+        write_batch(&mut batch)?;
+    }
+    writers.into_iter().try_for_each(ChunkedWriter::finish)?;
+
+    if batch.nulled > 0 {
+        eprintln!(
+            "Warning: {} cells didn't match the column types sampled from the first {} rows and were written as null, raise --stream-sample to sample more",
+            batch.nulled, settings.sample_rows,
+        );
+    }
+    if batch.dropped > 0 {
+        eprintln!("Warning: {} cells outside the sampled columns were left out", batch.dropped);
+    }
+    Ok(())
 }
 
 
-fn extract_data(data_rows: &[Vec<Data>], header_len: usize) -> Vec<Vec<String>> {
-    data_rows
-        .iter()
-        .map(|row| {
-            let mut cells: Vec<String> = row.iter().map(|cell| cell.to_string()).collect();
-            cells.resize(header_len, String::new());
-            cells
-        })
-        .collect()
+/// Parses the command line, layered over the `--config` file if one is given.
+fn parse_args() -> Result<ArgMatches, Box<dyn Error>> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    // A lenient first pass finds --config even when required args live in the file
+    let first_pass = build_cli().ignore_errors(true).try_get_matches_from(&args);
+    let config_path = first_pass
+        .as_ref()
+        .ok()
+        .and_then(|m| m.get_one::<String>("config").cloned());
+    let (Some(config_path), Ok(first_pass)) = (config_path, first_pass) else {
+        return Ok(build_cli().get_matches_from(args));
+    };
+
+    let file_args = config::config_args(&config_path, &build_cli(), &first_pass)?;
+    let merged = args[..1].iter().cloned().chain(file_args).chain(args[1..].iter().cloned());
+    Ok(build_cli().get_matches_from(merged))
 }
 
-/// Processes a vector of header names to ensure uniqueness.
-///
-/// This function takes a vector of strings representing header names and processes them
-/// to ensure that each header name is unique. If a header name is empty, it is replaced
-/// with a default name in the format "Unnamed_{index}", where {index} is the position
-/// of the header in the input vector. If a header name already exists in the processed
-/// list, a suffix is appended to the name to make it unique, following the format
-/// "{base_name}_{suffix}".
-///
-/// # Parameters
-///
-/// - `headers`: A vector of strings containing the header names to be processed.
-///
-/// # Returns
-///
-/// A vector of strings containing the processed header names, ensuring that all names
-/// are unique. If there were any duplicates or empty names in the input, they will be
-/// modified accordingly.
-///
-/// # Example
+
+fn build_cli() -> Command {
+    Command::new("Excel Reader")
+        .version("1.0")
+        .author("YehorI")
+        .about("Excel worksheet processor")
+        .after_help("Exit codes:\n  \
+            0   success\n  \
+            1   other error\n  \
+            2   invalid command line\n  \
+            10  input file not found\n  \
+            11  worksheet not found\n  \
+            12  header row out of bounds\n  \
+            13  output could not be written\n  \
+            14  headers do not match --validate-schema\n  \
+            15  --timeout exceeded\n  \
+            16  shape does not match --assert-rows/--assert-cols/--min-rows/--max-rows-assert\n  \
+            17  --validate-unique keys repeat")
+        .arg(Arg::new("path")
+            .short('p')
+            .long("path")
+            .help("Path to the Excel file, a .csv or .tsv file, a .zip archive of workbooks, or a glob pattern such as \"data/2024-*.xlsx\", to read and stack")
+            .required_unless_present("dump-config"))
+        .arg(Arg::new("separator")
+            .long("separator")
+            .help("Field separator of .csv or .tsv input, a single character or \"tab\" [default: from the extension]"))
+        .arg(Arg::new("inner-glob")
+            .long("inner-glob")
+            .help("With a .zip input, only read entries matching this pattern, e.g. \"daily/*.xlsx\""))
+        .arg(Arg::new("worksheet")
+            .short('w')
+            .long("worksheet")
+            .help("Name of the worksheet to process")
+            .required(false))
+        .arg(Arg::new("exact-sheet")
+            .long("exact-sheet")
+            .help("Match --worksheet exactly, without ignoring extra or trailing whitespace")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("normalize-unicode")
+            .long("normalize-unicode")
+            .help("Compare sheet names and normalize header names in this Unicode form")
+            .value_parser(["nfc", "nfd", "nfkc", "nfkd"]))
+        .arg(Arg::new("normalize-cells")
+            .long("normalize-cells")
+            .help("With --normalize-unicode, also normalize text cell values")
+            .action(ArgAction::SetTrue)
+            .requires("normalize-unicode"))
+        .arg(Arg::new("limit-cell-length")
+            .long("limit-cell-length")
+            .help("Truncate text cells longer than this many characters, ending them in \"…\", to guard against corrupt files")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64).range(1..)))
+        .arg(Arg::new("table")
+            .long("table")
+            .help("Read the Excel table (ListObject) with this name, including its header")
+            .conflicts_with_all(["worksheet", "header"]))
+        .arg(Arg::new("named-range")
+            .long("named-range")
+            .help("Read the cells a workbook defined name points at, e.g. SalesData => Sheet1!$A$1:$D$100")
+            .conflicts_with_all(["worksheet", "table"]))
+        .arg(Arg::new("pivot-cache")
+            .long("pivot-cache")
+            .help("Read the source records cached with the pivot table of this name, when the workbook was saved with them")
+            .conflicts_with_all(["worksheet", "header", "table", "named-range", "range", "kv-mode", "explain", "dump-merges", "extract-notes", "output-template"]))
+        .arg(Arg::new("all-sheets")
+            .long("all-sheets")
+            .help("Read every worksheet into one DataFrame with a __sheet__ column")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["worksheet", "table", "named-range", "pivot-cache", "chunksize"]))
+        .arg(Arg::new("exclude-sheets")
+            .long("exclude-sheets")
+            .help("With --all-sheets, skip these comma-separated worksheets, e.g. \"Cover,Notes\"")
+            .requires("all-sheets"))
+        .arg(Arg::new("sheet-range")
+            .long("sheet-range")
+            .help("With --all-sheets, only read the sheets at these 0-based positions, end exclusive, e.g. \"2:6\" or \"2:\" for the rest")
+            .value_name("START:END")
+            .requires("all-sheets"))
+        .arg(Arg::new("union")
+            .long("union")
+            .help("With --all-sheets, a .zip or a glob input, union differing schemas instead of failing")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("header")
+            .short('t')
+            .long("header")
+            .help("Header row number")
+            .required(false))
+        .arg(Arg::new("header-from-frozen")
+            .long("header-from-frozen")
+            .help("Use the rows frozen at the top of the sheet as the header rows, or the first row when none are frozen")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["header", "table", "named-range", "pivot-cache", "range", "kv-mode", "stream"]))
+        .arg(Arg::new("auto-header")
+            .long("auto-header")
+            .help("Find the header row of each sheet read: filled takes the first all-text row about as wide as the table, type-change the last all-text row above the numbers and dates")
+            .value_parser(["filled", "type-change"])
+            .conflicts_with_all(["header", "header-from-frozen", "range", "anchor", "table", "named-range", "pivot-cache", "kv-mode", "stream"]))
+        .arg(Arg::new("first-row-header-per-sheet")
+            .long("first-row-header-per-sheet")
+            .help("With --all-sheets, find each sheet's header row below any title or note rows instead of using the same --header row everywhere")
+            .action(ArgAction::SetTrue)
+            .requires("all-sheets")
+            .conflicts_with_all(["header", "header-from-frozen", "range", "anchor", "auto-header"]))
+        .arg(Arg::new("range")
+            .long("range")
+            .help("Only read these cells of the worksheet, e.g. \"B3:E40\", or whole columns like \"A:C\"")
+            .conflicts_with_all(["table", "named-range"]))
+        .arg(Arg::new("anchor")
+            .long("anchor")
+            .help("Read from the first used cell (first-cell, the default), or with auto only the largest block of filled cells, leaving out stray cells like a title")
+            .value_parser(["first-cell", "auto"])
+            .conflicts_with_all(["range", "table", "named-range"]))
+        .arg(Arg::new("last-row")
+            .long("last-row")
+            .help("Stop reading at this 1-based Excel row, inclusive, e.g. before notes below the data")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .conflicts_with("table"))
+        .arg(Arg::new("header-gap")
+            .long("header-gap")
+            .help("Skip rows between non-adjacent --header rows, e.g. row 1 of \"0,2\", or read them as data")
+            .value_parser(["skip", "data"])
+            .default_value("skip"))
+        .arg(Arg::new("keep-header-in-data")
+            .long("keep-header-in-data")
+            .visible_alias("header-row-as-data")
+            .help("Also keep the header row(s) as the first data row(s), as they appear in the sheet")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("kv-mode")
+            .long("kv-mode")
+            .help("Read the sheet as key/value pairs, keys down the first column (rows) or across the first row (cols), into key and value columns")
+            .value_parser(["rows", "cols"])
+            .conflicts_with_all(["header", "table", "named-range", "all-sheets", "chunksize", "explain"]))
+        .arg(Arg::new("header-collapse")
+            .long("header-collapse")
+            .help("Name multi-row header columns by joining all parts, by the first or last non-empty part, or as a \"top::sub\" path that .json output nests")
+            .value_parser(["join", "first", "last", "nested"])
+            .default_value("join"))
+        .arg(Arg::new("header-sep")
+            .long("header-sep")
+            .help("Join the parts of multi-row header names with this text, may be empty [default: \" \"]"))
+        .arg(Arg::new("units-row")
+            .long("units-row")
+            .help("0-based row of units to append to the header names in parentheses, e.g. \"Temperature (°C)\"")
+            .value_parser(clap::value_parser!(usize))
+            .conflicts_with("table"))
+        .arg(Arg::new("header-fill-forward")
+            .long("header-fill-forward")
+            .help("Fill blank cells of the top header row from the label to their left, for merged category labels over a detail row")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("ffill-header-rows")
+            .long("ffill-header-rows")
+            .help("Fill blank cells of these header rows from the label to their left, comma-separated and counted from the top header row, e.g. 0,1")
+            .conflicts_with("header-fill-forward"))
+        .arg(Arg::new("empty-header-prefix")
+            .long("empty-header-prefix")
+            .help("Name columns without a header this prefix plus their index, may be empty [default: Unnamed_]"))
+        .arg(Arg::new("deterministic-unnamed")
+            .long("deterministic-unnamed")
+            .help("Name columns without a header after their worksheet column, e.g. Unnamed_C, instead of their position")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("coerce-headers-ascii")
+            .long("coerce-headers-ascii")
+            .visible_alias("ascii-headers")
+            .help("Transliterate header names to ASCII, e.g. \"Дата\" to \"Data\", replacing characters without a spelling by \"_\"")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("prefix-numeric-headers")
+            .long("prefix-numeric-headers")
+            .help("Prepend this prefix to header names that start with a digit, e.g. \"y_\" turns 2024 into y_2024"))
+        .arg(Arg::new("rename-regex")
+            .long("rename-regex")
+            .help("Rename headers matching a regex, e.g. \"^col_=>\" or \"(?<y>\\d{4})_(?<m>\\d+)=>${m}/${y}\" (repeatable)")
+            .action(ArgAction::Append))
+        .arg(Arg::new("formatted-values")
+            .long("formatted-values")
+            .help("Read number and date cells as the text Excel shows, e.g. \"(555) 123-4567\", in all columns or these comma-separated ones")
+            .value_name("COLUMNS")
+            .num_args(0..=1)
+            .default_missing_value("")
+            .conflicts_with("table"))
+        .arg(Arg::new("rename-map")
+            .long("rename-map")
+            .help("Rename headers from a JSON file of {\"old name\": \"new name\"} pairs, after --rename-regex"))
+        .arg(Arg::new("rename-strict")
+            .long("rename-strict")
+            .help("Fail instead of warning when --rename-map names a column the sheet doesn't have")
+            .action(ArgAction::SetTrue)
+            .requires("rename-map"))
+        .arg(Arg::new("formulas")
+            .long("formulas")
+            .help("Read formula cells as their cached value or as the formula text")
+            .value_parser(["value", "text"])
+            .default_value("value"))
+        .arg(Arg::new("coalesce-headers")
+            .long("coalesce-headers")
+            .help("Merge columns with the same header, taking the first non-empty value per row")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("strict-headers")
+            .long("strict-headers")
+            .help("Fail instead of warning when header rows have different widths")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("validate-schema")
+            .long("validate-schema")
+            .help("Fail unless the header names are exactly \"col1,col2,...\" in this order, or as listed in @file"))
+        .arg(Arg::new("decimal-columns")
+            .long("decimal-columns")
+            .help("Cast a column to Decimal, e.g. \"amount=18,2\" (repeatable)")
+            .action(ArgAction::Append)
+            .required(false))
+        .arg(Arg::new("decimal-overflow")
+            .long("decimal-overflow")
+            .help("What to do with values that have more fractional digits than the scale")
+            .value_parser(["error", "round"])
+            .default_value("error"))
+        .arg(Arg::new("visible-only")
+            .long("visible-only")
+            .help("Drop data rows hidden by Excel filters or row hiding")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["table", "pivot-cache", "kv-mode", "stream"]))
+        .arg(Arg::new("date-system")
+            .long("date-system")
+            .help("Count serial dates from 1900 or 1904, overriding the workbook's own setting (auto)")
+            .value_parser(["1900", "1904", "auto"])
+            .default_value("auto"))
+        .arg(Arg::new("use-formats")
+            .long("use-formats")
+            .help("Type date/time formatted columns from the cell number formats")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("index-col")
+            .long("index-col")
+            .help("Treat this column as row labels: always keep it as text, untouched by typing options")
+            .value_name("COLUMN"))
+        .arg(Arg::new("index-first")
+            .long("index-first")
+            .help("Move the --index-col column to the front")
+            .action(ArgAction::SetTrue)
+            .requires("index-col"))
+        .arg(Arg::new("strip-currency-symbols")
+            .long("strip-currency-symbols")
+            .help("Turn amounts with these comma-separated symbols, e.g. \"$,€,₽\", into plain numbers like 1234.56, reading \"1.234,56\" and \"1,234.56\" alike")
+            .value_name("SYMBOLS"))
+        .arg(Arg::new("strip-thousands")
+            .long("strip-thousands")
+            .help("Remove this thousands separator from numbers like \"1,234,567\", in columns where every text cell is such a number")
+            .value_name("SEPARATOR"))
+        .arg(Arg::new("infer-types")
+            .long("infer-types")
+            .help("Convert text columns whose values are all integers or floats to numeric columns")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("empty-string-as-null")
+            .long("empty-string-as-null")
+            .help("Read empty cells of text columns as null instead of \"\"")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("strict-numeric")
+            .long("strict-numeric")
+            .help("Fail instead of keeping text when a numeric column would change a value like \"1.10\"")
+            .action(ArgAction::SetTrue)
+            .requires("infer-types"))
+        .arg(Arg::new("schema-from-first-row")
+            .long("schema-from-first-row")
+            .help("Type each column like its cell in the first data row, later values that don't fit become empty")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["infer-types", "use-formats"]))
+        .arg(Arg::new("abort-on-mixed-dates")
+            .long("abort-on-mixed-dates")
+            .help("Fail when more than this share (0 to 1, default 0) of a date column's values aren't dates")
+            .value_name("THRESHOLD")
+            .num_args(0..=1)
+            .default_missing_value("0")
+            .value_parser(parse_share))
+        .arg(Arg::new("bool-true")
+            .long("bool-true")
+            .help("With --infer-types, read columns of these comma-separated values and --bool-false ones as booleans, e.g. \"Y,Да,1\"")
+            .requires("infer-types"))
+        .arg(Arg::new("bool-false")
+            .long("bool-false")
+            .help("Comma-separated values read as false, e.g. \"N,Нет,0\"")
+            .requires("infer-types"))
+        .arg(Arg::new("bool-ignore-case")
+            .long("bool-ignore-case")
+            .help("Match --bool-true and --bool-false values regardless of case")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("bool-unmatched")
+            .long("bool-unmatched")
+            .help("Keep a column with values that are not boolean tokens as text, or make it boolean when most values are, leaving the rest empty")
+            .value_parser(["text", "null"])
+            .default_value("text"))
+        .arg(Arg::new("big-int-policy")
+            .long("big-int-policy")
+            .help("With --infer-types, keep integers too large for 64 bits as text, read them as f64 or fail")
+            .value_parser(["string", "f64", "error"])
+            .default_value("string")
+            .requires("infer-types"))
+        .arg(Arg::new("explode-delimited")
+            .long("explode-delimited")
+            .help("Split a column on a separator into one row per value, e.g. \"tags:;\" (repeatable)")
+            .action(ArgAction::Append))
+        .arg(Arg::new("row-hash")
+            .long("row-hash")
+            .help("Append a column with this name holding a 64-bit XXH3 hash of each row's cell text, for change detection")
+            .value_name("COLUMN"))
+        .arg(Arg::new("columns")
+            .long("columns")
+            .help("Keep only these comma-separated columns, e.g. \"Region,Sales\", each with fallback names or 0-based positions, e.g. \"Sales|@4\"")
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("explain-lazy")
+            .long("explain-lazy")
+            .help("Print the optimized Polars plan for --columns and --group-agg instead of running them")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["melt", "pivot", "fill-na", "date-format", "count-by", "count-distinct", "chunksize", "explain"]))
+        .arg(Arg::new("columns-order")
+            .long("columns-order")
+            .help("Put --columns in the order they are listed, or in their sheet order")
+            .value_parser(["input", "sheet"])
+            .default_value("input"))
+        .arg(Arg::new("fill-na")
+            .long("fill-na")
+            .help("Replace nulls and empty cells with a value, for all columns or per column as \"amount=0,region=unknown\""))
+        .arg(Arg::new("date-format")
+            .long("date-format")
+            .visible_alias("cast-dates-to-string")
+            .help("Write date and datetime columns as text in this strftime format, e.g. \"%Y-%m-%d\""))
+        .arg(Arg::new("round")
+            .long("round")
+            .help("Round these float columns to a number of decimals before writing, e.g. \"margin:2,rate:4\"")
+            .value_name("COLUMN:DECIMALS")
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("count-by")
+            .long("count-by")
+            .help("Print row counts per value of these comma-separated columns instead of the data")
+            .conflicts_with_all(["output", "output-template"]))
+        .arg(Arg::new("count-distinct")
+            .long("count-distinct")
+            .help("Print how many distinct non-null values these comma-separated columns take together, and whether they are unique, instead of the data")
+            .conflicts_with_all(["output", "output-template", "output-dataset", "count-by"]))
+        .arg(Arg::new("pivot")
+            .long("pivot")
+            .help("Reshape long to wide, e.g. \"index=Region;columns=Month;values=Sales;agg=sum\" (agg: first, last, sum, min, max, mean, median, count)")
+            .conflicts_with_all(["count-by", "chunksize"]))
+        .arg(Arg::new("melt")
+            .long("melt")
+            .help("Reshape wide to long, e.g. \"id=Region;columns=2023,2024;var-name=year;value-name=sales\" (columns default to all non-id ones)")
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("group-agg")
+            .long("group-agg")
+            .help("Summarize per group, e.g. \"by=Region;sum=Sales,Units;mean=Margin\" (sum, mean, median, min, max, first, last, count, n_unique)")
+            .conflicts_with_all(["count-by", "chunksize"]))
+        .arg(Arg::new("drop-duplicate-rows")
+            .long("drop-duplicate-rows")
+            .help("Drop rows that repeat an earlier row in every column")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("drop-duplicate-rows-on")
+            .long("drop-duplicate-rows-on")
+            .help("Drop rows that repeat an earlier row in these comma-separated columns, e.g. \"Id,Date\"")
+            .conflicts_with_all(["drop-duplicate-rows", "chunksize"]))
+        .arg(Arg::new("keep")
+            .long("keep")
+            .help("Which of a set of duplicate rows to keep")
+            .value_parser(["first", "last"])
+            .default_value("first"))
+        .arg(Arg::new("assert-rows")
+            .long("assert-rows")
+            .help("Fail with exit code 16 unless the result has exactly this many rows")
+            .value_parser(clap::value_parser!(usize))
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("assert-cols")
+            .long("assert-cols")
+            .help("Fail with exit code 16 unless the result has exactly this many columns")
+            .value_parser(clap::value_parser!(usize))
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("min-rows")
+            .long("min-rows")
+            .help("Fail with exit code 16 if the result has fewer rows")
+            .value_parser(clap::value_parser!(usize))
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("max-rows-assert")
+            .long("max-rows-assert")
+            .help("Fail with exit code 16 if the result has more rows")
+            .value_parser(clap::value_parser!(usize))
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("validate-unique")
+            .long("validate-unique")
+            .help("Fail with exit code 17 if non-null values of this column, or combinations of these comma-separated columns, repeat, listing them with their counts")
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("head-cols")
+            .long("head-cols")
+            .help("Show at most this many columns in the preview, the output keeps all of them")
+            .value_parser(clap::value_parser!(usize)))
+        .arg(Arg::new("errors-to")
+            .long("errors-to")
+            .help("Leave cells that fail to convert empty and list them in this file (data row from 0, column, value, reason)")
+            .conflicts_with("all-sheets"))
+        .arg(Arg::new("extract-notes")
+            .long("extract-notes")
+            .help("Also write the cell notes of the sheet(s) to this .csv or .parquet file, keyed by cell"))
+        .arg(Arg::new("output")
+            .short('o')
+            .long("output")
+            .help("Write the result to a .csv, .parquet, .xlsx, .json or .md file, or an s3://, gs:// or az:// URL, instead of printing a preview (repeatable). URLs need excel_reader built with the provider's feature: aws, gcp or azure")
+            .action(ArgAction::Append)
+            .required(false))
+        .arg(Arg::new("output-dataset")
+            .long("output-dataset")
+            .help("Also write the result as a Hive-partitioned Parquet dataset into this new or empty directory")
+            .value_name("DIR")
+            .requires("partition-by")
+            .conflicts_with_all(["count-by", "chunksize", "output-template"]))
+        .arg(Arg::new("partition-by")
+            .long("partition-by")
+            .help("Comma-separated columns that make up the --output-dataset directories, e.g. \"year,region\"")
+            .requires("output-dataset"))
+        .arg(Arg::new("json-pretty")
+            .long("json-pretty")
+            .help("Indent .json output")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("markdown-max-rows")
+            .long("markdown-max-rows")
+            .help("Cut .md output off after this many rows, with a note")
+            .value_parser(clap::value_parser!(usize)))
+        .arg(Arg::new("markdown-max-cols")
+            .long("markdown-max-cols")
+            .help("Cut .md output off after this many columns, with a note")
+            .value_parser(clap::value_parser!(usize)))
+        .arg(Arg::new("profile-out")
+            .long("profile-out")
+            .help("Also write per-column type, null and distinct counts, min/max and sample values to this JSON file")
+            .conflicts_with("chunksize"))
+        .arg(Arg::new("output-sheet")
+            .long("output-sheet")
+            .help("Worksheet name for .xlsx output, defaults to Sheet1 or the source sheet name with --all-sheets")
+            .requires("target"))
+        .arg(Arg::new("output-template")
+            .long("output-template")
+            .help("Output path with {stem}, {sheet} and {date} placeholders, one file per sheet with --all-sheets")
+            .conflicts_with_all(["output", "union"]))
+        .arg(Arg::new("dry-output")
+            .long("dry-output")
+            .help("Print the files the run would write, one per line, without writing them")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("chunksize")
+            .long("chunksize")
+            .help("Convert and write this many data rows at a time, typing columns from the first chunk (requires --output). The worksheet's cells are still loaded whole, use --stream to read it cell by cell")
+            .value_parser(clap::value_parser!(usize))
+            .requires("target"))
+        .arg(Arg::new("stream")
+            .long("stream")
+            .help("With --chunksize, read the worksheet cell by cell instead of loading it, typing columns from the first --stream-sample rows")
+            .action(ArgAction::SetTrue)
+            .requires("chunksize")
+            .conflicts_with_all(["table", "named-range", "pivot-cache", "range", "anchor", "last-row", "units-row", "keep-header-in-data", "header-fill-forward", "ffill-header-rows", "formatted-values", "use-formats", "explain"]))
+        .arg(Arg::new("stream-sample")
+            .long("stream-sample")
+            .help("Data rows --stream reads ahead to settle the column types, later cells that don't fit are written as null [default: 1000]")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .requires("stream"))
+        .arg(Arg::new("type-report")
+            .long("type-report")
+            .help("Print each column's type, how many values it was decided from and why, e.g. why a column stayed text, instead of the data")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["all-sheets", "explain", "stream"]))
+        .arg(Arg::new("write-schema-sidecar")
+            .long("write-schema-sidecar")
+            .help("Also write each .csv output's column names and types to \"<output>.schema.json\"")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("cell-type-stats")
+            .long("cell-type-stats")
+            .help("Print how many cells of each column are empty, text, numbers, booleans, dates or errors as read from the sheet, instead of the data")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["all-sheets", "explain", "stream", "type-report"]))
+        .arg(Arg::new("atomic-output")
+            .long("atomic-output")
+            .help("Write each output to \"<path>.tmp\" and rename it to the path only once it is complete. An --output-dataset directory that already exists is removed just before the rename, so it is missing for a moment")
+            .action(ArgAction::SetTrue))
+        .group(ArgGroup::new("target").args(["output", "output-template"]))
+        .arg(Arg::new("max-threads")
+            .long("max-threads")
+            .help("Use at most this many worker threads instead of one per core")
+            .value_parser(clap::value_parser!(usize)))
+        .arg(Arg::new("dump-merges")
+            .long("dump-merges")
+            .help("Print the merged cell ranges of the sheet, one A1 range per line, and exit")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("all-sheets"))
+        .arg(Arg::new("headers-only")
+            .long("headers-only")
+            .help("Print the final column names, one per line or as a JSON array, and exit without converting the data")
+            .value_name("FORMAT")
+            .num_args(0..=1)
+            .default_missing_value("lines")
+            .value_parser(["lines", "json"])
+            .conflicts_with_all(["all-sheets", "kv-mode", "explain"]))
+        .arg(Arg::new("explain")
+            .long("explain")
+            .help("Print the sheet, headers, column types, steps and outputs a run would use, without converting the data")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("all-sheets"))
+        .arg(Arg::new("timeout")
+            .long("timeout")
+            .help("Give up with exit code 15 when processing takes longer than this many seconds")
+            .value_name("SECONDS")
+            .value_parser(clap::value_parser!(u64).range(1..)))
+        .arg(Arg::new("config")
+            .long("config")
+            .help("Read settings from a TOML file, command line flags take precedence")
+            .required(false))
+        .arg(Arg::new("dump-config")
+            .long("dump-config")
+            .help("Print the settings given on the command line and in --config as TOML and exit")
+            .action(ArgAction::SetTrue))
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(Command::new("completions")
+            .about("Print a shell completion script to stdout")
+            .hide(true)
+            .arg(Arg::new("shell")
+                .help("Target shell")
+                .required(true)
+                .value_parser(clap::value_parser!(Shell))))
+}
+
+
+/// Writes the cells `--errors-to` collected, one row each, and warns how many there were.
+fn write_error_log(log: &ErrorLog, path: &str, atomic: bool) -> Result<(), Box<dyn Error>> {
+    let mut df = log.to_dataframe()?;
+    if df.height() > 0 {
+        eprintln!("Warning: {} cells could not be converted and were left empty, see '{}'", df.height(), path);
+    }
+    output::write_dataframe(&mut df, path, &WriteOptions { sheet_name: Some("Errors"), atomic, ..Default::default() })
+}
+
+
+fn process_excel_worksheet(
+    path: &str,
+    worksheet_name: Option<&str>,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let sheet = load_sheet(path, worksheet_name, header_rows, options)?;
+    sheet_to_dataframe(&sheet, options)
+}
+
+
+/// Reads every non-empty worksheet into its own DataFrame, keyed by sheet name.
+fn read_all_sheets(
+    path: &str,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+) -> Result<Vec<(String, DataFrame)>, Box<dyn Error>> {
+    let mut workbook = open_excel(path)?;
+    let mut frames = Vec::new();
+    for name in all_sheet_names(workbook.sheet_names(), options)? {
+        let range = workbook::worksheet_range(&mut workbook, Some(&name), options.sheet_match, options.formulas)?;
+        if range.is_empty() {
+            eprintln!("Warning: skipping empty worksheet '{}'", name);
+            continue;
+        }
+        let package = sheet::sheet_package(path, || Ok(name.clone()), options)?;
+        // Each sheet may freeze a different number of rows or start its table lower
+        let sheet_headers = match &header_rows {
+            None if options.header_from_frozen => sheet::frozen_header_rows(path, &name, &range)?,
+            None if let Some(mode) = options.auto_header => Some(vec![sheet::auto_header_row(&range, mode, &name)]),
+            header_rows => header_rows.clone(),
+        };
+        let sheet = sheet::sheet_from_range(&range, sheet_headers, &package, options)?;
+        frames.push((name, sheet_to_dataframe(&sheet, options)?));
+    }
+
+    if frames.is_empty() {
+        return Err("No non-empty worksheets found in the workbook".into());
+    }
+    Ok(frames)
+}
+
+
+/// The worksheets `--all-sheets` reads, in workbook order, after `--sheet-range` and `--exclude-sheets`.
+fn all_sheet_names(names: Vec<String>, options: &FrameOptions) -> Result<Vec<String>, Box<dyn Error>> {
+    // Excluded names are matched like --worksheet, so the same spelling works for both
+    let mut excluded = Vec::new();
+    for requested in &options.exclude_sheets {
+        match find_sheet_name(&names, requested, options.sheet_match) {
+            Ok(name) => excluded.push(name),
+            Err(e) if e.downcast_ref::<ExcelReaderError>().is_some() => {
+                eprintln!("Warning: --exclude-sheets names '{}', which is not in the workbook", requested);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    let positions = match options.sheet_range {
+        Some((start, end)) => {
+            let end = end.unwrap_or(names.len());
+            if end > names.len() || start >= end {
+                return Err(format!("--sheet-range goes past the last sheet, the workbook has {} sheets", names.len()).into());
+            }
+            start..end
+        }
+        None => 0..names.len(),
+    };
+    Ok(names[positions].iter().filter(|name| !excluded.contains(name)).cloned().collect())
+}
+
+
+/// Reads every non-empty worksheet and stacks them with a `__sheet__` column.
+fn process_all_sheets(
+    path: &str,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+    union: bool,
+) -> Result<DataFrame, Box<dyn Error>> {
+    stack_frames(read_all_sheets(path, header_rows, options)?, "__sheet__", union)
+}
+
+
+/// Reads the same worksheet from every workbook in a .zip archive and stacks them with a `__file__` column.
+fn process_archive(
+    path: &str,
+    inner_glob: Option<&str>,
+    worksheet_name: Option<&str>,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+    union: bool,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let mut archive = archive::archive_workbooks(path, inner_glob)?;
+    let mut frames = Vec::new();
+    for (name, index) in archive.entries.clone() {
+        let sheet = archive
+            .open(index)
+            .and_then(|mut workbook| archived_sheet(&mut workbook, worksheet_name, header_rows.clone(), options));
+        match sheet.and_then(|sheet| sheet_to_dataframe(&sheet, options)) {
+            Ok(df) => frames.push((name, df)),
+            Err(e) => {
+                // Keep the error itself, and with it the exit code, but say which entry failed
+                eprintln!("Failed to read '{}'", name);
+                return Err(e);
+            }
+        }
+    }
+    stack_frames(frames, "__file__", union)
+}
+
+
+/// Loads a worksheet of a workbook read from an archive.
 ///
-/// ```
-/// let headers = vec!["Header1".to_string(), "".to_string(), "Header1".to_string()];
-/// let processed = process_headers(headers);
-/// assert_eq!(processed, vec!["Header1", "Unnamed_1", "Header1_1"]);
-/// ```
+/// The workbook is only in memory, so options that read the package by
+/// path, like `--header-from-frozen`, are not available.
+fn archived_sheet(
+    workbook: &mut Xlsx<std::io::Cursor<Vec<u8>>>,
+    worksheet_name: Option<&str>,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+) -> Result<SheetData, Box<dyn Error>> {
+    let range = workbook::worksheet_range(workbook, worksheet_name, options.sheet_match, options.formulas)?;
+    let header_rows = match header_rows {
+        None if let Some(mode) = options.auto_header => {
+            let sheet_name = match worksheet_name {
+                Some(requested) => find_sheet_name(&workbook.sheet_names(), requested, options.sheet_match)?,
+                None => workbook.sheet_names().first().cloned().unwrap_or_default(),
+            };
+            Some(vec![sheet::auto_header_row(&range, mode, &sheet_name)])
+        }
+        header_rows => header_rows,
+    };
+    sheet::sheet_from_range(&range, header_rows, &SheetPackage::default(), options)
+}
+
+
+/// Reads the same worksheet of every `(name, path)` workbook and stacks them with a `__file__` column.
+fn process_files(
+    files: &[(String, &str)],
+    worksheet_name: Option<&str>,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+    union: bool,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let mut frames = Vec::new();
+    for (name, file) in files {
+        match process_excel_worksheet(file, worksheet_name, header_rows.clone(), options) {
+            Ok(df) => frames.push((name.clone(), df)),
+            Err(e) => {
+                // Keep the error itself, and with it the exit code, but say which file failed
+                eprintln!("Failed to read '{}'", name);
+                return Err(e);
+            }
+        }
+    }
+    stack_frames(frames, "__file__", union)
+}
+
+
+/// Stacks named DataFrames, recording each row's source name in `column`.
 ///
-/// # Panics
+/// Frames must share a schema unless `union` is set, in which case missing
+/// columns are filled with nulls.
+fn stack_frames(named: Vec<(String, DataFrame)>, column: &str, union: bool) -> Result<DataFrame, Box<dyn Error>> {
+    let mut frames = Vec::new();
+    for (name, mut df) in named {
+        let source_column = Series::new(column.into(), vec![name.as_str(); df.height()]);
+        df.insert_column(0, source_column)?;
+        frames.push(df);
+    }
+    if union {
+        return Ok(polars::functions::concat_df_diagonal(&frames)?);
+    }
+    let mut frames = frames.into_iter();
+    let mut combined = frames.next().unwrap();
+    for df in frames {
+        combined.vstack_mut(&df)?;
+    }
+    Ok(combined)
+}
+
+
+/// The `--type-report` text: each column's type, the values its type was decided from, and why.
+fn type_report(sheet: &SheetData, options: &FrameOptions) -> Result<String, Box<dyn Error>> {
+    validate_headers(&sheet.headers, options)?;
+    let plan = plan_types(sheet, options)?;
+    // The types come from the plan alone, so converting no rows gives the final schema
+    let empty = convert_rows(&sheet.headers, &[], 0, &plan, options)?;
+    let mut text = String::new();
+    for ((name, dtype), reason) in empty.schema().iter().zip(&plan.reasons) {
+        text.push_str(&format!("{}: {} from {} values, {}\n", name, dtype, reason.sampled, reason.reason));
+    }
+    Ok(text)
+}
+
+
+/// Cell kinds `--cell-type-stats` counts, named after calamine's `Data` variants.
+const CELL_KINDS: [&str; 9] = ["Empty", "String", "Float", "Int", "Bool", "DateTime", "DateTimeIso", "DurationIso", "Error"];
+
+/// Counts the cells of each kind per column, one row per column, for `--cell-type-stats`.
 ///
-/// This function does not panic under normal circumstances, but it assumes that the
-/// input vector is not excessively large, as it uses a hash set to track used names.
-/// 
-/// # Complexity
+/// The cells are those the data rows hold before any conversion, cells
+/// missing from short rows count as empty.
+fn cell_type_stats(sheet: &SheetData) -> PolarsResult<DataFrame> {
+    let names = process_headers(sheet.headers.clone());
+    let mut counts = vec![[0u32; CELL_KINDS.len()]; names.len()];
+    for row in sheet.data_rows() {
+        for (col_idx, column) in counts.iter_mut().enumerate() {
+            let kind = match row.get(col_idx) {
+                None | Some(Data::Empty) => 0,
+                Some(Data::String(_)) => 1,
+                Some(Data::Float(_)) => 2,
+                Some(Data::Int(_)) => 3,
+                Some(Data::Bool(_)) => 4,
+                Some(Data::DateTime(_)) => 5,
+                Some(Data::DateTimeIso(_)) => 6,
+                Some(Data::DurationIso(_)) => 7,
+                Some(Data::Error(_)) => 8,
+            };
+            column[kind] += 1;
+        }
+    }
+    let mut columns = vec![Column::new("column".into(), names)];
+    for (kind_idx, kind) in CELL_KINDS.iter().enumerate() {
+        columns.push(Column::new((*kind).into(), counts.iter().map(|column| column[kind_idx]).collect::<Vec<_>>()));
+    }
+    DataFrame::new(columns)
+}
+
+
+fn load_sheet(
+    path: &str,
+    worksheet_name: Option<&str>,
+    header_rows: Option<Vec<usize>>, // <-- modified type
+    options: &FrameOptions,
+) -> Result<SheetData, Box<dyn Error>> {
+    let parts = sheet::worksheet_parts(path, worksheet_name, header_rows, options)?;
+    sheet::sheet_from_range(&parts.range, parts.header_rows, &parts.package, options)
+}
+
+
+/// Loads a worksheet for `--chunksize`, copying `chunksize` data rows at a time out of its cells.
 ///
-/// The function has a time complexity of O(n) where n is the number of headers, as it
-/// iterates through the list and performs constant-time operations for each header.
-fn process_headers(headers: Vec<String>) -> Vec<String> {
-    let mut processed_headers = Vec::with_capacity(headers.len());
-    let mut used_names = PlHashSet::new();
-
-    for (i, header) in headers.iter().enumerate() {
-        let base_name = if header.is_empty() {
-            format!("Unnamed_{}", i)
-        } else {
-            header.clone()
-        };
+/// The first chunk comes even without data rows, for the header.
+fn load_sheet_chunks<'a>(
+    path: &str,
+    worksheet_name: Option<&str>,
+    header_rows: Option<Vec<usize>>,
+    options: &'a FrameOptions,
+    chunksize: usize,
+) -> Result<impl Iterator<Item = Result<SheetData, Box<dyn Error>>> + 'a, Box<dyn Error>> {
+    let sheet::WorksheetParts { range, header_rows, package } = sheet::worksheet_parts(path, worksheet_name, header_rows, options)?;
+    let sliced = match sheet::selected_range(&range, options) {
+        Cow::Owned(sliced) => Some(sliced),
+        Cow::Borrowed(_) => None,
+    };
+    let range = sliced.unwrap_or(range);
+    let height = sheet::selected_height(&range, options);
+    let head = header_rows.iter().flatten().chain(&options.units_row).max().copied().unwrap_or(0) + 1;
+    let mut next = Some(head);
+    Ok(std::iter::from_fn(move || {
+        let first = next?;
+        let body = first..(first + chunksize).min(height.max(first));
+        next = Some(body.end).filter(|&end| end < height);
+        Some(sheet::sheet_rows_from_range(&range, header_rows.clone(), &package, options, Some(body)))
+    }))
+}
+
+
+/// Reads the cells a defined name points at, with header rows counted from its top.
+fn load_named_range(
+    path: &str,
+    name: &str,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+) -> Result<SheetData, Box<dyn Error>> {
+    let mut workbook = open_excel(path)?;
+    let area = workbook::named_range(&workbook, name)?;
+    let range = workbook::worksheet_range(&mut workbook, Some(&area.sheet), SheetMatch { exact: true, unicode: None }, options.formulas)?;
+    let package = sheet::sheet_package(path, || Ok(area.sheet.clone()), options)?;
+    sheet::sheet_from_range(&workbook::slice_range(&range, area.start, area.end), header_rows, &package, options)
+}
 
-        let mut candidate = base_name.clone();
-        let mut suffix = 0;
 
-        // Generate a unique candidate name
-        while used_names.contains(&candidate) {
-            suffix += 1;
-            candidate = format!("{}_{}", base_name, suffix);
+/// Reads an Excel table (ListObject) by name, using its own header row.
+fn load_table(path: &str, table_name: &str, options: &FrameOptions) -> Result<SheetData, Box<dyn Error>> {
+    let mut workbook = open_excel(path)?;
+    workbook.load_tables()?;
+    let table = match workbook.table_by_name(table_name) {
+        Ok(table) => table,
+        Err(XlsxError::TableNotFound(_)) => {
+            let names = workbook.table_names().into_iter().cloned().collect::<Vec<_>>().join(", ");
+            return Err(format!("Table '{}' not found, available tables: [{}]", table_name, names).into());
         }
+        Err(e) => return Err(e.into()),
+    };
 
-        used_names.insert(candidate.clone());
-        processed_headers.push(candidate);
+    let mut headers = table.columns().to_vec();
+    let mut rows: Vec<Vec<Data>> = table.data().rows().map(|row| row.to_vec()).collect();
+    if options.keep_header_in_data {
+        rows.insert(0, headers.iter().map(|name| Data::String(name.clone())).collect());
     }
-    processed_headers
+    sheet::limit_cell_length(&mut rows, options);
+    formats::apply_date_system(&mut rows, options.date_system);
+    sheet::strip_currency_symbols(&mut rows, options);
+    sheet::strip_thousands(&mut rows, options);
+    sheet::normalize_unicode(&mut headers, &mut rows, options);
+    sheet::coerce_headers_ascii(&mut headers, options);
+    sheet::prefix_numeric_headers(&mut headers, options);
+    sheet::rename_headers(&mut headers, options)?;
+    let sheet = SheetData {
+        headers,
+        rows,
+        data_start: 0,
+    };
+    Ok(if options.coalesce_headers { sheet::coalesce_columns(sheet) } else { sheet })
 }
 
 
-fn create_dataframe(headers: Vec<String>, data: Vec<Vec<String>>) -> Result<DataFrame, PolarsError> {
-    let headers = process_headers(headers);
-    let columns: Vec<Column> = (0..headers.len())
-        .into_par_iter()
-        .map(|i| {
-            let col_data: Vec<&str> = data.iter().map(|row| row[i].as_str()).collect();
-            let series = Series::new(headers[i].clone().into(), &col_data);
-            series.into()
-        })
-        .collect();
-    Ok(DataFrame::new(columns)?)
+/// Reads the source records cached with a pivot table, named by its cache fields.
+fn load_pivot_cache(path: &str, pivot_name: &str, options: &FrameOptions) -> Result<SheetData, Box<dyn Error>> {
+    let PivotCache { fields: mut headers, records: mut rows } = pivot_cache::read_pivot_cache(path, pivot_name)?;
+    if options.keep_header_in_data {
+        rows.insert(0, headers.iter().map(|name| Data::String(name.clone())).collect());
+    }
+    sheet::limit_cell_length(&mut rows, options);
+    sheet::strip_currency_symbols(&mut rows, options);
+    sheet::strip_thousands(&mut rows, options);
+    sheet::normalize_unicode(&mut headers, &mut rows, options);
+    sheet::coerce_headers_ascii(&mut headers, options);
+    sheet::prefix_numeric_headers(&mut headers, options);
+    sheet::rename_headers(&mut headers, options)?;
+    Ok(SheetData {
+        headers,
+        rows,
+        data_start: 0,
+    })
+}
+
+
+/// Where `--kv-mode` finds the keys and their values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum KeyValueLayout {
+    /// Keys down the first column, values in the second
+    Rows,
+    /// Keys across the first row, values in the second
+    Cols,
+}
+
+
+/// Reads a worksheet of key/value pairs into `key` and `value` text columns, for `--kv-mode`.
+///
+/// Only the first two columns (or rows) are read. Pairs where both cells
+/// are empty are skipped, so blank spacer lines don't show up.
+fn load_key_values(
+    path: &str,
+    worksheet_name: Option<&str>,
+    layout: KeyValueLayout,
+    options: &FrameOptions,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let range = sheet::get_worksheet_range(path, worksheet_name, options)?;
+    let rows = sheet::selected_rows(&sheet::selected_range(&range, options), 0, None, options);
+    let pairs: Vec<(Option<&Data>, Option<&Data>)> = match layout {
+        KeyValueLayout::Rows => rows.iter().map(|row| (row.first(), row.get(1))).collect(),
+        KeyValueLayout::Cols => {
+            let keys = rows.first().map(|row| row.as_slice()).unwrap_or_default();
+            let values = rows.get(1).map(|row| row.as_slice()).unwrap_or_default();
+            (0..keys.len()).map(|i| (keys.get(i), values.get(i))).collect()
+        }
+    };
+    let (mut keys, mut values) = (Vec::new(), Vec::new());
+    let mut buffer = String::new();
+    for (key, value) in pairs {
+        let key = cell_text(key, &mut buffer).to_string();
+        let value = cell_text(value, &mut buffer).to_string();
+        if !key.is_empty() || !value.is_empty() {
+            keys.push(key);
+            values.push(value);
+        }
+    }
+    Ok(DataFrame::new(vec![Column::new("key".into(), keys), Column::new("value".into(), values)])?)
+}
+
+
+/// Parses `--separator`, a single ASCII character or "tab".
+fn parse_separator(value: &str) -> Result<u8, Box<dyn Error>> {
+    match value {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!("Invalid --separator '{}', expected a single character or \"tab\"", value).into()),
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use excel_reader::display;
+    use std::collections::HashSet;
     use std::error;
 
     #[test]
     fn it_works() -> Result<(), Box<dyn error::Error>>{
-        let path: &str = "/home/yehori/Documents/Projects/Rust learning/excel_reader/src/test.xlsx";
+        let path: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/test.xlsx");
         let worksheet_name: &str = "МАЙ  2024";
-        let df = process_excel_worksheet(path, Some(worksheet_name), None)?;
+        let df = process_excel_worksheet(path, Some(worksheet_name), None, &FrameOptions::default())?;
         assert_eq!(df.shape().0, 2100);
         Ok(())
     }
+
+    #[test]
+    fn exit_codes_stay_clear_of_usage_errors() {
+        let usage = build_cli().try_get_matches_from(["excel_reader", "--no-such-flag"]).unwrap_err();
+        assert_eq!(usage.exit_code(), 2);
+        let (_, code) = failure(&ExcelReaderError::FileNotFound("missing.xlsx".to_string()));
+        assert_eq!(code, 10);
+        let (_, code) = failure(&*Box::<dyn Error>::from("anything else"));
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn unique_check_counts_repeated_keys() -> Result<(), Box<dyn error::Error>> {
+        let df = df!(
+            "Id" => [Some(1), Some(1), Some(2), Some(2), None, None],
+            "Year" => [2023, 2023, 2023, 2024, 2023, 2023],
+        )?;
+        let error = check_unique(&df, &["Id".to_string(), "Year".to_string()]).unwrap_err();
+        assert_eq!(error.to_string(), "Key values are not unique: 1 values of Id, Year repeat, in 2 rows");
+        let df = df!("count" => [3, 3, 5], "count_1" => ["a", "a", "b"])?;
+        let error = check_unique(&df, &["count".to_string(), "count_1".to_string()]).unwrap_err();
+        assert_eq!(error.to_string(), "Key values are not unique: 1 values of count, count_1 repeat, in 2 rows");
+        Ok(())
+    }
+
+    #[test]
+    fn reads_first_sheet_of_multi_sheet_workbook() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("sheets.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        for (name, value) in [("First", "a"), ("Second", "b")] {
+            let sheet = book.add_worksheet().set_name(name)?;
+            sheet.write_string(0, 0, "col")?;
+            sheet.write_string(1, 0, value)?;
+        }
+        book.save(&path)?;
+
+        let path = path.to_str().unwrap();
+        let df = process_excel_worksheet(path, None, None, &FrameOptions::default())?;
+        assert_eq!(df.column("col")?.get(0)?, AnyValue::String("a"));
+        assert_eq!(sheet::resolve_sheet_name(path, None, None, &FrameOptions::default())?, "First");
+        let df = process_excel_worksheet(path, Some("Second"), None, &FrameOptions::default())?;
+        assert_eq!(df.column("col")?.get(0)?, AnyValue::String("b"));
+        Ok(())
+    }
+
+    #[test]
+    fn use_formats_types_date_columns() -> Result<(), Box<dyn error::Error>> {
+        let path: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/test.xlsx");
+        let options = FrameOptions { use_formats: true, ..Default::default() };
+        let df = process_excel_worksheet(path, Some("МАЙ  2024"), None, &options)?;
+        assert_eq!(df.column("Дата")?.dtype(), &DataType::Date);
+        assert_eq!(df.column("п/п")?.dtype(), &DataType::String);
+        assert_eq!(df.column("Время въезда на Объект Исполнителя")?.dtype(), &DataType::Time);
+
+        // The same serial is 1462 days later counted from 1904
+        let options = FrameOptions { use_formats: true, date_system: DateSystem::Excel1904, ..Default::default() };
+        let df = process_excel_worksheet(path, Some("МАЙ  2024"), None, &options)?;
+        assert_eq!(df.column("Дата")?.get(0)?, AnyValue::Date(19844 + 1462));
+        Ok(())
+    }
+
+    #[test]
+    fn reads_dataframe_with_metadata() -> Result<(), Box<dyn error::Error>> {
+        let path: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/test.xlsx");
+        let options = excel_reader::FrameOptions { use_formats: true, infer_types: true, ..Default::default() };
+        let (df, meta) = excel_reader::read_excel_with_meta(path, &options)?;
+        assert_eq!(meta.sheet, "МАЙ  2024");
+        assert_eq!(meta.header_rows, vec![0]);
+        assert_eq!(meta.header_map[1], ("Дата".to_string(), "Дата".to_string()));
+        assert_eq!(meta.dtypes[1], ("Дата".to_string(), DataType::Date));
+        assert_eq!(meta.dtypes.len(), df.width());
+        assert_eq!(meta.type_reasons[1], ("Дата".to_string(), "every value is date formatted".to_string()));
+        // The same conversion as the command line's
+        assert!(df.equals_missing(&process_excel_worksheet(path, None, None, &options)?));
+
+        let options = excel_reader::FrameOptions { auto_header: Some(AutoHeader::Filled), ..Default::default() };
+        let (df, meta) = excel_reader::read_excel_with_meta(path, &options)?;
+        assert_eq!(meta.header_rows, vec![0]);
+        assert!(df.equals_missing(&process_excel_worksheet(path, None, None, &options)?));
+        Ok(())
+    }
+
+    #[test]
+    fn parses_sheet_ranges() {
+        assert_eq!(parse_sheet_range("2:6").ok(), Some((2, Some(6))));
+        assert_eq!(parse_sheet_range("2:").ok(), Some((2, None)));
+        assert_eq!(parse_sheet_range(":3").ok(), Some((0, Some(3))));
+        assert!(parse_sheet_range("3:3").is_err());
+        assert!(parse_sheet_range("2").is_err());
+    }
+
+    #[test]
+    fn finds_header_row_per_sheet() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("offset.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let first = book.add_worksheet().set_name("Plain")?;
+        first.write_row(0, 0, ["id", "amount"])?;
+        first.write_row(1, 0, [1, 2])?;
+        let second = book.add_worksheet().set_name("Titled")?;
+        second.write_string(0, 0, "Quarterly report")?;
+        second.write_string(1, 0, "Source: finance")?;
+        second.write_row(3, 0, ["id", "amount"])?;
+        second.write_row(4, 0, [3, 4])?;
+        second.write_row(5, 0, [5, 6])?;
+        book.save(&path)?;
+
+        let options = FrameOptions { auto_header: Some(AutoHeader::Filled), ..Default::default() };
+        let frames = read_all_sheets(path.to_str().unwrap(), None, &options)?;
+        for (name, df) in &frames {
+            assert_eq!(df.get_column_names_str(), ["id", "amount"], "{}", name);
+        }
+        assert_eq!(frames[1].1.height(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn counts_cell_kinds_per_column() -> Result<(), Box<dyn error::Error>> {
+        let rows = vec![
+            vec![Data::String("id".into()), Data::String("value".into())],
+            vec![Data::Int(1), Data::Float(2.5)],
+            vec![Data::Float(2.0), Data::Error(calamine::CellErrorType::NA)],
+            vec![Data::String("3".into())],
+        ];
+        let sheet = SheetData { headers: vec!["id".into(), "value".into()], rows, data_start: 1 };
+        let stats = cell_type_stats(&sheet)?;
+        let counts = |kind: &str| stats.column(kind).unwrap().u32().unwrap().to_vec();
+        assert_eq!(counts("Int"), [Some(1), Some(0)]);
+        assert_eq!(counts("Float"), [Some(1), Some(1)]);
+        assert_eq!(counts("String"), [Some(1), Some(0)]);
+        assert_eq!(counts("Error"), [Some(0), Some(1)]);
+        assert_eq!(counts("Empty"), [Some(0), Some(1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_delimited_files_like_sheets() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("export.tsv");
+        std::fs::write(&path, "Export\nid\tname\tname\n1\tNorth\t\n2\t\"South,\tEast\"\tx\n")?;
+        let path = path.to_str().unwrap();
+        assert_eq!(delimited::separator_for(path), Some(b'\t'));
+        let options = FrameOptions { separator: Some(b'\t'), infer_types: true, ..Default::default() };
+        let df = process_excel_worksheet(path, None, Some(vec![1]), &options)?;
+        assert_eq!(df.get_column_names_str(), ["id", "name", "name_1"]);
+        assert_eq!(df.column("id")?.i64()?.to_vec(), [Some(1), Some(2)]);
+        assert_eq!(df.column("name")?.str()?.get(1), Some("South,\tEast"));
+        assert_eq!(sheet::resolve_sheet_name(path, None, None, &options)?, "export");
+        Ok(())
+    }
+
+    #[test]
+    fn visible_only_drops_hidden_data_rows() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("hidden.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_string(0, 0, "id")?;
+        for row in 1..=4 {
+            sheet.write_number(row, 0, row as f64)?;
+        }
+        sheet.set_row_hidden(2)?;
+        sheet.set_row_hidden(4)?;
+        book.save(&path)?;
+
+        let path = path.to_str().unwrap();
+        assert_eq!(display::hidden_rows(path, "Sheet1")?, HashSet::from([2, 4]));
+        let options = FrameOptions { visible_only: true, ..Default::default() };
+        let sheet = load_sheet(path, None, None, &options)?;
+        assert_eq!(sheet.data_rows(), [vec![Data::Float(1.0)], vec![Data::Float(3.0)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn sheet_chunks_match_the_whole_sheet() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("chunks.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_string(0, 0, "id")?;
+        sheet.write_string(1, 0, "note")?;
+        sheet.write_string(2, 0, "unit")?;
+        for row in 3..=9 {
+            sheet.write_number(row, 0, row as f64)?;
+        }
+        sheet.set_row_hidden(5)?;
+        sheet.set_row_hidden(8)?;
+        book.save(&path)?;
+
+        let path = path.to_str().unwrap();
+        let options = FrameOptions { visible_only: true, header_gap_as_data: true, ..Default::default() };
+        let whole = load_sheet(path, None, Some(vec![0, 2]), &options)?;
+        let chunks = load_sheet_chunks(path, None, Some(vec![0, 2]), &options, 2)?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|chunk| chunk.headers == whole.headers));
+        let rows: Vec<Vec<Data>> = chunks.iter().flat_map(|chunk| chunk.data_rows().to_vec()).collect();
+        assert_eq!(rows, whole.data_rows());
+        assert_eq!(rows.len(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_pivot_cache_records() -> Result<(), Box<dyn error::Error>> {
+        use std::io::Write;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("pivot.xlsx");
+        let rel = |kind: &str, target: &str| format!(
+            r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/{}" Target="{}"/></Relationships>"#,
+            kind, target,
+        );
+        let parts = [
+            ("xl/pivotTables/pivotTable1.xml", r#"<pivotTableDefinition name="SalesPivot"/>"#.to_string()),
+            ("xl/pivotTables/_rels/pivotTable1.xml.rels", rel("pivotCacheDefinition", "../pivotCache/pivotCacheDefinition1.xml")),
+            ("xl/pivotCache/pivotCacheDefinition1.xml", concat!(
+                r#"<pivotCacheDefinition><cacheFields count="2">"#,
+                r#"<cacheField name="Region"><sharedItems><s v="North"/><s v="South"/></sharedItems>"#,
+                r#"<fieldGroup><groupItems><s v="Group1"/></groupItems></fieldGroup></cacheField>"#,
+                r#"<cacheField name="Sales"><sharedItems containsNumber="1"/></cacheField>"#,
+                r#"</cacheFields></pivotCacheDefinition>"#,
+            ).to_string()),
+            ("xl/pivotCache/_rels/pivotCacheDefinition1.xml.rels", rel("pivotCacheRecords", "pivotCacheRecords1.xml")),
+            ("xl/pivotCache/pivotCacheRecords1.xml", concat!(
+                r#"<pivotCacheRecords count="3"><r><x v="1"/><n v="20"/></r>"#,
+                r#"<r><x v="0"/><n v="10.5"/></r><r><x v="1"/><m/></r></pivotCacheRecords>"#,
+            ).to_string()),
+        ];
+        let mut zip = ::zip::ZipWriter::new(std::fs::File::create(&path)?);
+        for (name, xml) in parts {
+            zip.start_file(name, ::zip::write::SimpleFileOptions::default())?;
+            zip.write_all(xml.as_bytes())?;
+        }
+        zip.finish()?;
+
+        let path = path.to_str().unwrap();
+        let options = FrameOptions { infer_types: true, ..Default::default() };
+        let df = sheet_to_dataframe(&load_pivot_cache(path, "SalesPivot", &options)?, &options)?;
+        assert_eq!(df.get_column_names_str(), ["Region", "Sales"]);
+        assert_eq!(df.column("Region")?.str()?.into_no_null_iter().collect::<Vec<_>>(), ["South", "North", "South"]);
+        assert_eq!(df.column("Sales")?.f64()?.into_iter().collect::<Vec<_>>(), [Some(20.0), Some(10.5), None]);
+        let error = load_pivot_cache(path, "Other", &FrameOptions::default()).err().unwrap();
+        assert_eq!(error.to_string(), "Pivot table 'Other' not found, available pivot tables: [SalesPivot]");
+        Ok(())
+    }
+
+    #[test]
+    fn streams_rows_with_sampled_types() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("stream.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_string(0, 1, "Report")?;
+        sheet.write_row(2, 1, ["id", "amount"])?;
+        sheet.write_row(3, 1, [1.0, 2.5])?;
+        sheet.write_row(4, 1, [2.0, 4.0])?;
+        sheet.write_string(6, 1, "n/a")?;
+        sheet.write_number(6, 2, 1.0)?;
+        book.save(&path)?;
+
+        let output = dir.path().join("out.csv").to_str().unwrap().to_string();
+        let settings = StreamSettings { batch_rows: 2, sample_rows: 2 };
+        let create = |schema: &Schema| Ok(vec![ChunkedWriter::create(&output, schema, &WriteOptions { atomic: true, ..Default::default() })?]);
+        stream_worksheet(path.to_str().unwrap(), "Sheet1", 2, create, &FrameOptions::default(), settings, Ok)?;
+        assert!(!std::path::Path::new(&output::temp_path(&output)).exists());
+        // The empty row 6 stays, text in the integer column past the sample is null
+        assert_eq!(std::fs::read_to_string(&output)?, "id,amount\n1,2.5\n2,4.0\n,\n,1.0\n");
+        Ok(())
+    }
+
+    #[test]
+    fn frozen_rows_become_header_rows() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("frozen.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet().set_name("Frozen")?;
+        sheet.write_row(1, 0, ["Sales", "Sales"])?;
+        sheet.write_row(2, 0, ["2023", "2024"])?;
+        sheet.write_row(3, 0, [1, 2])?;
+        sheet.set_freeze_panes(3, 0)?;
+        book.add_worksheet().set_name("Plain")?.write_row(0, 0, ["a", "b"])?;
+        book.save(&path)?;
+
+        let path = path.to_str().unwrap();
+        let options = FrameOptions { header_from_frozen: true, ..Default::default() };
+        // Excel rows 1 to 3 are frozen, the used range starts at row 2
+        let sheet = load_sheet(path, Some("Frozen"), None, &options)?;
+        assert_eq!(sheet.headers, ["Sales 2023", "Sales 2024"]);
+        assert_eq!(sheet.data_rows().len(), 1);
+        assert_eq!(display::frozen_rows(path, "Plain")?, None);
+        assert_eq!(load_sheet(path, Some("Plain"), None, &options)?.headers, ["a", "b"]);
+        Ok(())
+    }
+
+    #[test]
+    fn type_report_explains_text_fallbacks() -> Result<(), Box<dyn error::Error>> {
+        let text = |s: &str| Data::String(s.to_string());
+        let rows = vec![
+            vec![text("id"), text("code")],
+            vec![text("1"), text("7")],
+            vec![text("2"), text("n/a")],
+        ];
+        let sheet = SheetData { headers: vec!["id".into(), "code".into()], rows, data_start: 1 };
+        let options = FrameOptions { infer_types: true, ..Default::default() };
+        assert_eq!(
+            type_report(&sheet, &options)?,
+            "id: i64 from 2 values, every value is an integer\n\
+             code: str from 2 values, mixed: number '7' at data row 1 and text 'n/a' at data row 2\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn formulas_text_shows_formulas_over_cached_values() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("formulas.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_string(0, 0, "total")?;
+        sheet.write_number(1, 0, 2.0)?;
+        sheet.write_formula(2, 0, rust_xlsxwriter::Formula::new("=A2*2").set_result("4"))?;
+        book.save(&path)?;
+
+        let path = path.to_str().unwrap();
+        let values = load_sheet(path, None, None, &FrameOptions::default())?;
+        assert_eq!(values.data_rows(), [vec![Data::Float(2.0)], vec![Data::Float(4.0)]]);
+        let options = FrameOptions { formulas: FormulaMode::Text, ..Default::default() };
+        let formulas = load_sheet(path, None, None, &options)?;
+        // Plain values have no formula and keep their cell
+        assert_eq!(formulas.data_rows(), [vec![Data::Float(2.0)], vec![Data::String("=A2*2".to_string())]]);
+        Ok(())
+    }
+
+    #[test]
+    fn loads_a_table_by_name() -> Result<(), Box<dyn error::Error>> {
+        use rust_xlsxwriter::{Table, TableColumn};
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("tables.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_string(0, 0, "Quarterly sales")?;
+        sheet.write_string(3, 1, "North")?;
+        sheet.write_number(3, 2, 10.0)?;
+        sheet.write_string(4, 1, "South")?;
+        sheet.write_number(4, 2, 20.0)?;
+        let columns = [TableColumn::new().set_header("Region"), TableColumn::new().set_header("2024")];
+        sheet.add_table(2, 1, 4, 2, &Table::new().set_name("Sales").set_columns(&columns))?;
+        book.save(&path)?;
+
+        let path = path.to_str().unwrap();
+        let options = FrameOptions { numeric_header_prefix: Some("y".to_string()), ..Default::default() };
+        let table = load_table(path, "Sales", &options)?;
+        assert_eq!(table.headers, vec!["Region", "y2024"]);
+        assert_eq!(table.data_rows(), [
+            vec![Data::String("North".to_string()), Data::Float(10.0)],
+            vec![Data::String("South".to_string()), Data::Float(20.0)],
+        ]);
+        let err = load_table(path, "Costs", &options).err().map(|err| err.to_string());
+        assert_eq!(err.as_deref(), Some("Table 'Costs' not found, available tables: [Sales]"));
+        Ok(())
+    }
+
+    #[test]
+    fn decimal_columns_follow_the_overflow_policy() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("prices.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_row(0, 0, ["item", "price"])?;
+        sheet.write_string(1, 0, "tea")?;
+        sheet.write_number(1, 1, 12.5)?;
+        sheet.write_row(2, 0, ["milk", "-0.125"])?;
+        book.save(&path)?;
+
+        let path = path.to_str().unwrap();
+        let options = |policy| FrameOptions {
+            infer_types: true,
+            decimal_columns: vec![parse_decimal_spec("price=9,2").unwrap()],
+            decimal_policy: policy,
+            ..Default::default()
+        };
+        let err = process_excel_worksheet(path, None, None, &options(DecimalPolicy::Error)).unwrap_err();
+        assert_eq!(err.to_string(), "Column 'price': '-0.125' has more than 2 fractional digits");
+
+        let df = process_excel_worksheet(path, None, None, &options(DecimalPolicy::Round))?;
+        assert_eq!(df.column("price")?.dtype(), &DataType::Decimal(Some(9), Some(2)));
+        let prices: Vec<AnyValue> = df.column("price")?.as_materialized_series().iter().collect();
+        assert_eq!(prices, [AnyValue::Decimal(1250, 2), AnyValue::Decimal(-13, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn kv_mode_rows_reads_pairs_down_the_sheet() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_row(0, 0, ["Name", "Ann", "ignored"])?;
+        sheet.write_string(2, 0, "Age")?;
+        sheet.write_number(2, 1, 42.0)?;
+        sheet.write_string(3, 0, "Notes")?;
+        book.save(&path)?;
+
+        // The blank row 2 is skipped, a key without a value is kept
+        let df = load_key_values(path.to_str().unwrap(), None, KeyValueLayout::Rows, &FrameOptions::default())?;
+        assert_eq!(df.get_column_names_str(), ["key", "value"]);
+        assert_eq!(df.column("key")?.str()?.into_no_null_iter().collect::<Vec<_>>(), ["Name", "Age", "Notes"]);
+        assert_eq!(df.column("value")?.str()?.into_no_null_iter().collect::<Vec<_>>(), ["Ann", "42", ""]);
+        Ok(())
+    }
+
+    #[test]
+    fn kv_mode_cols_reads_pairs_across_the_sheet() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet();
+        sheet.write_row(0, 0, ["Name", "", "Age"])?;
+        sheet.write_string(1, 0, "Ann")?;
+        sheet.write_number(1, 2, 42.0)?;
+        sheet.write_row(2, 0, ["ignored", "ignored"])?;
+        book.save(&path)?;
+
+        let df = load_key_values(path.to_str().unwrap(), None, KeyValueLayout::Cols, &FrameOptions::default())?;
+        assert_eq!(df.column("key")?.str()?.into_no_null_iter().collect::<Vec<_>>(), ["Name", "Age"]);
+        assert_eq!(df.column("value")?.str()?.into_no_null_iter().collect::<Vec<_>>(), ["Ann", "42"]);
+        Ok(())
+    }
+
+    #[test]
+    fn big_int_policy_needs_infer_types() {
+        let parse = |args: &[&str]| build_cli().try_get_matches_from([&["excel_reader", "-p", "a.xlsx"], args].concat());
+        assert!(parse(&["--big-int-policy", "f64"]).is_err());
+        assert!(parse(&["--big-int-policy", "f64", "--infer-types"]).is_ok());
+        // The default alone doesn't ask for --infer-types
+        assert!(parse(&[]).is_ok());
+    }
+
+    #[test]
+    fn reads_archived_workbooks_in_memory() -> Result<(), Box<dyn error::Error>> {
+        use std::io::Write;
+        let dir = tempfile::tempdir()?;
+        let workbook = |id: f64| -> Result<Vec<u8>, rust_xlsxwriter::XlsxError> {
+            let mut book = rust_xlsxwriter::Workbook::new();
+            let sheet = book.add_worksheet();
+            sheet.write_string(0, 0, "id")?;
+            sheet.write_number(1, 0, id)?;
+            book.save_to_buffer()
+        };
+        let path = dir.path().join("daily.zip");
+        let mut zip = ::zip::ZipWriter::new(std::fs::File::create(&path)?);
+        let entries = [
+            ("daily/a.xlsx", workbook(1.0)?),
+            ("daily/2024/b.xlsx", workbook(2.0)?),
+            ("monthly/c.xlsx", workbook(3.0)?),
+            ("daily/~$a.xlsx", Vec::new()),
+            ("daily/readme.txt", b"not a workbook".to_vec()),
+        ];
+        for (name, bytes) in entries {
+            zip.start_file(name, ::zip::write::SimpleFileOptions::default())?;
+            zip.write_all(&bytes)?;
+        }
+        zip.finish()?;
+
+        let path = path.to_str().unwrap();
+
+        let df = process_archive(path, None, None, None, &FrameOptions { infer_types: true, ..Default::default() }, false)?;
+        let files: Vec<&str> = df.column("__file__")?.str()?.into_no_null_iter().collect();
+        assert_eq!(files, ["daily/a.xlsx", "daily/2024/b.xlsx", "monthly/c.xlsx"]);
+        assert_eq!(df.column("id")?.i64()?.to_vec(), [Some(1), Some(2), Some(3)]);
+        Ok(())
+    }
+
+    #[test]
+    fn notes_dataframe_lists_one_row_per_note() -> Result<(), Box<dyn error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("notes.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet().set_name("Data")?;
+        sheet.write_string(0, 0, "id")?;
+        sheet.set_default_note_author("Ann");
+        sheet.insert_note(2, 1, &rust_xlsxwriter::Note::new("Check this").add_author_prefix(false))?;
+        book.save(&path)?;
+
+        let df = notes_dataframe(path.to_str().unwrap(), &["Data".to_string()])?;
+        assert_eq!(df.get_column_names_str(), ["sheet", "cell", "row", "column", "author", "note"]);
+        assert_eq!(df.column("cell")?.str()?.get(0), Some("B3"));
+        assert_eq!(df.column("row")?.u32()?.get(0), Some(3));
+        Ok(())
+    }
 }
@@ -0,0 +1,293 @@
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use zip::ZipArchive;
+
+use crate::error::ExcelReaderError;
+use crate::workbook::parse_cell_ref;
+
+
+const COMMENTS_REL: &str = "/relationships/comments";
+const OFFICE_DOCUMENT_REL: &str = "/relationships/officeDocument";
+
+
+/// A cell note (legacy comment) with its 1-based sheet position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    pub cell: String,
+    pub row: u32,
+    pub col: u32,
+    pub author: String,
+    pub text: String,
+}
+
+
+/// Reads the notes attached to cells of the sheet called `sheet_name`.
+///
+/// calamine doesn't read comments, so this follows the package relationships
+/// from the workbook to the sheet's comments part and parses it directly.
+/// Sheets without notes give an empty list.
+pub fn read_notes(path: &str, sheet_name: &str) -> Result<Vec<Note>, Box<dyn Error>> {
+    let mut zip = open_package(path)?;
+    let Some(sheet_part) = sheet_part(&mut zip, sheet_name)? else {
+        return Ok(Vec::new());
+    };
+
+    let comment_parts: Vec<String> = relationships(&mut zip, &sheet_part)?
+        .into_iter()
+        .filter(|rel| rel.kind.ends_with(COMMENTS_REL))
+        .map(|rel| rel.target)
+        .collect();
+
+    let mut notes = Vec::new();
+    for part in comment_parts {
+        notes.extend(parse_comments(&read_part(&mut zip, &part)?)?);
+    }
+    Ok(notes)
+}
+
+
+/// Opens a workbook as the zip package it is, to read parts calamine doesn't.
+pub(crate) fn open_package(path: &str) -> Result<ZipArchive<BufReader<File>>, Box<dyn Error>> {
+    let file = File::open(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => Box::new(ExcelReaderError::FileNotFound(path.to_string())) as Box<dyn Error>,
+        _ => e.into(),
+    })?;
+    Ok(ZipArchive::new(BufReader::new(file))?)
+}
+
+
+/// The package path of the workbook part, found through the package's root relationships.
+///
+/// Excel names it "xl/workbook.xml", other writers may not.
+pub(crate) fn workbook_part<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<String, Box<dyn Error>> {
+    relationships(zip, "")?
+        .into_iter()
+        .find(|rel| rel.kind.ends_with(OFFICE_DOCUMENT_REL))
+        .map(|rel| rel.target)
+        .ok_or_else(|| "The package has no workbook part, is it a workbook?".into())
+}
+
+
+/// The package path of the sheet called exactly `sheet_name`, e.g. "xl/worksheets/sheet1.xml".
+pub(crate) fn sheet_part<R: Read + Seek>(zip: &mut ZipArchive<R>, sheet_name: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let workbook = workbook_part(zip)?;
+    let sheet_rel = attribute_pairs(zip, &workbook, b"sheet", b"name", b"r:id")?
+        .into_iter()
+        .find(|(name, _)| name == sheet_name)
+        .map(|(_, rel)| rel)
+        .ok_or_else(|| ExcelReaderError::SheetNotFound(sheet_name.to_string()))?;
+    Ok(relationships(zip, &workbook)?
+        .into_iter()
+        .find(|rel| rel.id == sheet_rel)
+        .map(|rel| rel.target))
+}
+
+
+pub(crate) fn read_part<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Result<String, Box<dyn Error>> {
+    let mut text = String::new();
+    zip.by_name(name)
+        .map_err(|e| format!("Cannot read '{}' from the workbook: {}", name, e))?
+        .read_to_string(&mut text)?;
+    Ok(text)
+}
+
+
+/// The `(key, value)` attribute pairs of every `element` in a package part.
+fn attribute_pairs<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    part: &str,
+    element: &[u8],
+    key: &[u8],
+    value: &[u8],
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let xml = read_part(zip, part)?;
+    let mut reader = Reader::from_str(&xml);
+    let mut pairs = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == element => {
+                let decoder = reader.decoder();
+                if let (Some(k), Some(v)) = (attribute(&e, key, decoder)?, attribute(&e, value, decoder)?) {
+                    pairs.push((k, v));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(pairs)
+}
+
+
+pub(crate) fn attribute(element: &BytesStart, key: &[u8], decoder: Decoder) -> Result<Option<String>, Box<dyn Error>> {
+    match element.try_get_attribute(key)? {
+        Some(attr) => Ok(Some(attr.decode_and_unescape_value(decoder)?.into_owned())),
+        None => Ok(None),
+    }
+}
+
+
+/// A link from one package part to another, e.g. from a sheet to its comments.
+pub(crate) struct Relationship {
+    pub id: String,
+    pub kind: String,
+    pub target: String,
+}
+
+/// The relationships of `part`, with targets resolved to package paths.
+///
+/// The empty part is the package itself. A part without a relationships
+/// file has no relationships.
+pub(crate) fn relationships<R: Read + Seek>(zip: &mut ZipArchive<R>, part: &str) -> Result<Vec<Relationship>, Box<dyn Error>> {
+    let (dir, file_name) = part.rsplit_once('/').unwrap_or(("", part));
+    let rels_part = match dir {
+        "" => format!("_rels/{}.rels", file_name),
+        dir => format!("{}/_rels/{}.rels", dir, file_name),
+    };
+    if zip.by_name(&rels_part).is_err() {
+        return Ok(Vec::new());
+    }
+    let xml = read_part(zip, &rels_part)?;
+    let mut reader = Reader::from_str(&xml);
+    let mut rels = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"Relationship" => {
+                let get = |key: &[u8]| attribute(&e, key, reader.decoder()).map(Option::unwrap_or_default);
+                rels.push(Relationship {
+                    id: get(b"Id")?,
+                    kind: get(b"Type")?,
+                    target: resolve_target(dir, &get(b"Target")?),
+                });
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(rels)
+}
+
+
+/// Resolves a relationship target like "../comments1.xml" against the directory of its source part.
+fn resolve_target(dir: &str, target: &str) -> String {
+    let mut segments: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => dir.split('/').filter(|s| !s.is_empty()).collect(),
+    };
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+
+/// Parses a comments part into notes, joining the text runs of each comment.
+fn parse_comments(xml: &str) -> Result<Vec<Note>, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    let mut authors: Vec<String> = Vec::new();
+    let mut notes = Vec::new();
+    // The element whose text is being collected: an author or a comment's <t>
+    let mut in_author = false;
+    let mut in_text = false;
+    let mut current: Option<(String, usize, String)> = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"author" => {
+                    in_author = true;
+                    authors.push(String::new());
+                }
+                b"comment" => {
+                    let cell = attribute(&e, b"ref", reader.decoder())?.unwrap_or_default();
+                    let author = attribute(&e, b"authorId", reader.decoder())?
+                        .and_then(|id| id.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    current = Some((cell, author, String::new()));
+                }
+                b"t" => in_text = true,
+                _ => {}
+            },
+            Event::Text(t) => {
+                let text = t.unescape()?;
+                if in_author {
+                    if let Some(author) = authors.last_mut() {
+                        author.push_str(&text);
+                    }
+                } else if let (true, Some((_, _, note))) = (in_text, current.as_mut()) {
+                    note.push_str(&text);
+                }
+            }
+            Event::End(e) => match e.name().as_ref() {
+                b"author" => in_author = false,
+                b"t" => in_text = false,
+                b"comment" => {
+                    if let Some((cell, author, text)) = current.take() {
+                        let (row, col) = parse_cell_ref(&cell)
+                            .map(|(row, col)| (row + 1, col + 1))
+                            .ok_or_else(|| format!("Invalid cell reference '{}' in comments", cell))?;
+                        let author = authors.get(author).cloned().unwrap_or_default();
+                        notes.push(Note { cell, row, col, author, text: text.trim().to_string() });
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_notes_through_the_package_relationships() -> Result<(), Box<dyn Error>> {
+        use std::io::{Read, Write};
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("notes.xlsx");
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let sheet = book.add_worksheet().set_name("Data")?;
+        sheet.write_string(0, 0, "id")?;
+        sheet.set_default_note_author("Ann");
+        sheet.insert_note(2, 1, &rust_xlsxwriter::Note::new("Check this").add_author_prefix(false))?;
+        book.save(&path)?;
+
+        // Another writer may name the workbook part differently, the root relationships still point to it
+        let renamed = dir.path().join("renamed.xlsx");
+        let mut source = ::zip::ZipArchive::new(std::fs::File::open(&path)?)?;
+        let mut zip = ::zip::ZipWriter::new(std::fs::File::create(&renamed)?);
+        for i in 0..source.len() {
+            let mut entry = source.by_index(i)?;
+            let mut text = String::new();
+            entry.read_to_string(&mut text)?;
+            zip.start_file(entry.name().replace("workbook.xml", "book.xml"), ::zip::write::SimpleFileOptions::default())?;
+            zip.write_all(text.replace("workbook.xml", "book.xml").as_bytes())?;
+        }
+        zip.finish()?;
+
+        for path in [&path, &renamed] {
+            let path = path.to_str().unwrap();
+            let expected = Note {
+                cell: "B3".to_string(),
+                row: 3,
+                col: 2,
+                author: "Ann".to_string(),
+                text: "Check this".to_string(),
+            };
+            assert_eq!(read_notes(path, "Data")?, [expected]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,996 @@
+use polars::io::csv::write::BatchedWriter as CsvBatchedWriter;
+use polars::io::cloud::CloudOptions;
+use polars::io::parquet::write::BatchedWriter as ParquetBatchedWriter;
+use polars::io::WriteDataFrameToFile;
+use polars::prelude::*;
+use rust_xlsxwriter::{Format, Workbook};
+use std::error::Error;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use excel_reader::decimal::format_decimal;
+use excel_reader::sheet::NESTED_SEPARATOR;
+
+use crate::error::ExcelReaderError;
+
+
+/// Output file formats, picked from the output path's extension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Csv,
+    Parquet,
+    Xlsx,
+    Json,
+    Markdown,
+}
+
+impl OutputFormat {
+    pub fn from_path(path: &str) -> Result<Self, Box<dyn Error>> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("csv") => Ok(OutputFormat::Csv),
+            Some("parquet") => Ok(OutputFormat::Parquet),
+            Some("xlsx") => Ok(OutputFormat::Xlsx),
+            Some("json") => Ok(OutputFormat::Json),
+            Some("md") => Ok(OutputFormat::Markdown),
+            _ => Err(format!("Unsupported output format for '{}', expected .csv, .parquet, .xlsx, .json or .md", path).into()),
+        }
+    }
+}
+
+
+/// Expands `{stem}`, `{sheet}` and `{date}` in an output path template.
+///
+/// `{stem}` is the input file name without its extension and `{date}` is
+/// today's date as YYYY-MM-DD. Substituted values are made safe for file
+/// names, the template text itself is kept as written.
+pub fn expand_template(template: &str, input_path: &str, sheet: &str) -> Result<String, Box<dyn Error>> {
+    let stem = Path::new(input_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        expanded.push_str(&rest[..open]);
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed placeholder in output template '{}'", template))?;
+        let value = match &rest[open + 1..open + close] {
+            "stem" => &stem,
+            "sheet" => sheet,
+            "date" => &date,
+            other => return Err(format!("Unknown placeholder '{{{}}}' in output template", other).into()),
+        };
+        expanded.push_str(&sanitize_file_name(value));
+        rest = &rest[open + close + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Replaces characters that are not allowed in file names on common platforms.
+fn sanitize_file_name(value: &str) -> String {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Collapse runs of whitespace, e.g. "МАЙ  2024" -> "МАЙ 2024"
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    match cleaned.as_str() {
+        "" | "." | ".." => "_".to_string(),
+        _ => cleaned,
+    }
+}
+
+
+/// Settings of `write_dataframe` that only some formats use.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WriteOptions<'a> {
+    /// Worksheet name of .xlsx output, "Sheet1" when not given
+    pub sheet_name: Option<&'a str>,
+    /// Indent .json output
+    pub json_pretty: bool,
+    /// Turn column names like "Q1::Sales" into nested .json objects
+    pub json_nested: bool,
+    /// Rows and columns of .md output past which the table is cut off with a note
+    pub markdown_max_rows: Option<usize>,
+    pub markdown_max_cols: Option<usize>,
+    /// Write to "<path>.tmp" first and rename it to the path once complete
+    pub atomic: bool,
+    /// Write the column types of .csv output to "<path>.schema.json"
+    pub schema_sidecar: bool,
+}
+
+
+/// Writes a whole DataFrame to `path` in the format implied by its extension.
+///
+/// `path` may also be an object store URL, see [`is_object_store_url`].
+pub fn write_dataframe(df: &mut DataFrame, path: &str, options: &WriteOptions) -> Result<(), Box<dyn Error>> {
+    if is_object_store_url(path) {
+        upload(df, path, options)?;
+    } else if !options.atomic {
+        write_file(df, path, path, options)?;
+    } else {
+        let temp = temp_path(path);
+        if let Err(e) = write_file(df, path, &temp, options) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e);
+        }
+        replace_with_temp(&temp, path)?;
+    }
+    // Only once the data is there, so a sidecar never describes a failed write
+    if options.schema_sidecar {
+        write_schema_sidecar(path, df.schema(), options.atomic)?;
+    }
+    Ok(())
+}
+
+
+/// The `--write-schema-sidecar` file of `path`.
+pub fn schema_sidecar_path(path: &str) -> String {
+    format!("{}.schema.json", path)
+}
+
+/// Writes the column names and types of a .csv output next to it, so a loader can cast the text back.
+///
+/// The types are Polars' names, e.g. "i64", "f64", "str", "date" or
+/// "datetime[μs]". Other formats keep their types and get no sidecar.
+pub fn write_schema_sidecar(path: &str, schema: &Schema, atomic: bool) -> Result<(), Box<dyn Error>> {
+    if OutputFormat::from_path(path)? != OutputFormat::Csv {
+        return Ok(());
+    }
+    let columns: Vec<serde_json::Value> = schema
+        .iter()
+        .map(|(name, dtype)| serde_json::json!({ "name": name.as_str(), "dtype": dtype.to_string() }))
+        .collect();
+    let sidecar = schema_sidecar_path(path);
+    if is_object_store_url(path) {
+        let bytes = serde_json::to_vec_pretty(&serde_json::json!({ "columns": columns }))?;
+        return upload_bytes(&sidecar, &bytes);
+    }
+    let target = if atomic { temp_path(&sidecar) } else { sidecar.clone() };
+    let file = File::create(&target).map_err(|e| ExcelReaderError::output_write(&sidecar, e))?;
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), &serde_json::json!({ "columns": columns }))
+        .map_err(|e| ExcelReaderError::output_write(&sidecar, e))?;
+    match atomic {
+        true => replace_with_temp(&target, &sidecar),
+        false => Ok(()),
+    }
+}
+
+
+/// The file `--atomic-output` writes before renaming it to `path`.
+///
+/// It sits next to `path`, so the rename stays on one file system and is atomic.
+pub fn temp_path(path: &str) -> String {
+    format!("{}.tmp", path)
+}
+
+/// Moves a finished temporary file over `path`.
+///
+/// `std::fs::rename` replaces an existing `path` on every platform, with
+/// `MoveFileExW(MOVEFILE_REPLACE_EXISTING)` on Windows, so readers see either
+/// the old file or the whole new one.
+fn replace_with_temp(temp: &str, path: &str) -> Result<(), Box<dyn Error>> {
+    std::fs::rename(temp, path).map_err(|e| {
+        let _ = std::fs::remove_file(temp);
+        ExcelReaderError::output_write(path, e).into()
+    })
+}
+
+
+/// Writes `df` to the file `target` in the format of `path`'s extension, naming `path` in errors.
+fn write_file(df: &mut DataFrame, path: &str, target: &str, options: &WriteOptions) -> Result<(), Box<dyn Error>> {
+    let format = OutputFormat::from_path(path)?;
+    if format == OutputFormat::Xlsx {
+        let saved = xlsx_workbook(df, options.sheet_name.unwrap_or("Sheet1")).and_then(|mut workbook| Ok(workbook.save(target)?));
+        return saved.map_err(|e| ExcelReaderError::output_write(path, e).into());
+    }
+    let file = File::create(target).map_err(|e| ExcelReaderError::output_write(path, e))?;
+    write_format(df, format, file, options).map_err(|e| ExcelReaderError::output_write(path, e))?;
+    Ok(())
+}
+
+
+/// Writes `df` to `writer` in any format but .xlsx, which needs to seek.
+fn write_format(df: &mut DataFrame, format: OutputFormat, writer: impl Write, options: &WriteOptions) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match format {
+        OutputFormat::Csv => CsvWriter::new(writer).finish(df)?,
+        OutputFormat::Parquet => {
+            ParquetWriter::new(writer).finish(df)?;
+        }
+        OutputFormat::Json => write_json(df, writer, options)?,
+        OutputFormat::Markdown => write_markdown(df, writer, options)?,
+        OutputFormat::Xlsx => return Err("an .xlsx file cannot be streamed".into()),
+    }
+    Ok(())
+}
+
+
+/// Object store URL schemes, with the crate feature that enables each provider.
+const OBJECT_STORES: [(&str, &str); 8] = [
+    ("s3://", "aws"),
+    ("s3a://", "aws"),
+    ("gs://", "gcp"),
+    ("gcs://", "gcp"),
+    ("az://", "azure"),
+    ("azure://", "azure"),
+    ("abfs://", "azure"),
+    ("abfss://", "azure"),
+];
+
+/// Whether `path` is an S3, Google Cloud Storage or Azure URL, e.g. "s3://bucket/key.parquet".
+pub fn is_object_store_url(path: &str) -> bool {
+    OBJECT_STORES.iter().any(|(scheme, _)| path.starts_with(scheme))
+}
+
+/// Fails unless this build has the provider feature for the scheme of `path`.
+fn check_object_store(path: &str) -> Result<(), Box<dyn Error>> {
+    let Some((scheme, feature)) = OBJECT_STORES.iter().find(|(scheme, _)| path.starts_with(scheme)) else {
+        return Ok(());
+    };
+    let enabled: &[&str] = &[
+        #[cfg(feature = "aws")]
+        "aws",
+        #[cfg(feature = "gcp")]
+        "gcp",
+        #[cfg(feature = "azure")]
+        "azure",
+    ];
+    match enabled.contains(feature) {
+        true => Ok(()),
+        false => Err(format!("Writing to {} URLs needs excel_reader built with the '{}' feature, e.g. cargo install --features {}", scheme, feature, feature).into()),
+    }
+}
+
+/// Writes `df` straight to an object store, without a local file.
+///
+/// The output is built in memory and uploaded through Polars' object store
+/// writer, which takes credentials and the region from the environment, e.g.
+/// AWS_ACCESS_KEY_ID or GOOGLE_APPLICATION_CREDENTIALS. Uploads only become
+/// visible once complete, so `atomic` makes no difference.
+fn upload(df: &mut DataFrame, path: &str, options: &WriteOptions) -> Result<(), Box<dyn Error>> {
+    let format = OutputFormat::from_path(path)?;
+    check_object_store(path)?;
+    let mut bytes = Vec::new();
+    let written = match format {
+        OutputFormat::Xlsx => xlsx_workbook(df, options.sheet_name.unwrap_or("Sheet1"))
+            .and_then(|mut workbook| Ok(workbook.save_to_buffer()?))
+            .map(|buffer| bytes = buffer),
+        _ => write_format(df, format, &mut bytes, options),
+    };
+    written.map_err(|e| ExcelReaderError::output_write(path, e))?;
+    upload_bytes(path, &bytes)
+}
+
+fn upload_bytes(path: &str, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    check_object_store(path)?;
+    let mut writer = polars::io::utils::file::try_get_writeable(path, None).map_err(|e| ExcelReaderError::output_write(path, e))?;
+    writer.write_all(bytes).and_then(|_| writer.close()).map_err(|e| ExcelReaderError::output_write(path, e))?;
+    Ok(())
+}
+
+
+/// Writes a Hive-partitioned Parquet dataset, e.g. `dir/year=2024/region=North/00000000.parquet`.
+///
+/// Partition values are percent-encoded where they would break the path
+/// ("/", "=", ":", " ", "%") and nulls go to `__HIVE_DEFAULT_PARTITION__`.
+/// The partition columns are only in the directory names, not in the files,
+/// as Spark expects. `dir` must not exist yet or be empty, so that no stale
+/// partition from an earlier run is picked up with the new ones. With
+/// `atomic` the dataset is written to "<dir>.tmp" and renamed once complete.
+/// That is not atomic when `dir` exists: the empty `dir` is removed first,
+/// so for a moment there is no `dir` at all.
+pub fn write_partitioned(df: &mut DataFrame, dir: &str, partition_by: &[String], atomic: bool) -> Result<(), Box<dyn Error>> {
+    if is_object_store_url(dir) {
+        return Err(format!("--output-dataset writes to a local directory only, '{}' is an object store URL", dir).into());
+    }
+    for column in partition_by {
+        if df.get_column_index(column).is_none() {
+            return Err(format!("--partition-by column '{}' not found", column).into());
+        }
+        if column.contains(['/', '\\', '=']) {
+            return Err(format!("--partition-by column '{}' can't be a directory name, rename it first", column).into());
+        }
+    }
+    if partition_by.len() == df.width() {
+        return Err("--partition-by needs at least one column left to write to the files".into());
+    }
+    if std::fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(format!("Dataset directory '{}' is not empty", dir).into());
+    }
+    let writer = PartitionFileWriter { partition_by, options: ParquetWriteOptions::default() };
+    let partition_by = partition_by.iter().map(|c| c.as_str().into()).collect();
+    let target = if atomic { temp_path(dir) } else { dir.to_string() };
+    if atomic && Path::new(&target).exists() {
+        return Err(format!("'{}' is left over from an earlier run, remove it first", target).into());
+    }
+    polars::io::partition::write_partitioned_dataset(df, Path::new(&target), partition_by, &writer, None, usize::MAX)
+        .map_err(|e| ExcelReaderError::output_write(dir, e))?;
+    if atomic {
+        // A directory can only be renamed over an empty one on Unix, so clear the way first
+        if Path::new(dir).exists() {
+            std::fs::remove_dir(dir).map_err(|e| ExcelReaderError::output_write(dir, e))?;
+        }
+        std::fs::rename(&target, dir).map_err(|e| ExcelReaderError::output_write(dir, e))?;
+    }
+    Ok(())
+}
+
+/// The files `write_partitioned` would create, sorted, for `--dry-output`.
+pub fn partition_files(df: &DataFrame, dir: &str, partition_by: &[String]) -> PolarsResult<Vec<String>> {
+    let keys = df.select(partition_by)?.unique_stable(None, UniqueKeepStrategy::First, None)?;
+    let columns: Vec<Series> = keys
+        .get_columns()
+        .iter()
+        .map(|c| c.as_materialized_series().cast(&DataType::String))
+        .collect::<PolarsResult<_>>()?;
+    let mut files = Vec::with_capacity(keys.height());
+    for row in 0..keys.height() {
+        let mut path = Path::new(dir).to_path_buf();
+        for series in &columns {
+            let value = series.str()?.get(row).unwrap_or("__HIVE_DEFAULT_PARTITION__");
+            path.push(format!("{}={}", series.name(), hive_encode(value)));
+        }
+        path.push("00000000.parquet");
+        files.push(path.to_string_lossy().into_owned());
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Percent-encodes a partition value the way Polars names its directories.
+fn hive_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'/' | b'=' | b':' | b' ' | b'%' | 0..0x20 | 0x7f.. => encoded.push_str(&format!("%{:02X}", byte)),
+            byte => encoded.push(byte as char),
+        }
+    }
+    encoded
+}
+
+/// Writes one partition's file without the columns its directory already names.
+struct PartitionFileWriter<'a> {
+    partition_by: &'a [String],
+    options: ParquetWriteOptions,
+}
+
+impl WriteDataFrameToFile for PartitionFileWriter<'_> {
+    fn write_df_to_file(&self, df: &mut DataFrame, path: &str, _: Option<&CloudOptions>) -> PolarsResult<()> {
+        let mut df = df.drop_many(self.partition_by);
+        self.options.to_writer(File::create(path)?).finish(&mut df)?;
+        Ok(())
+    }
+}
+
+
+/// Writes a DataFrame as a JSON array with one object per row.
+///
+/// Temporal values become their text form, decimals their exact digits as a
+/// string. With `json_nested` a column named "Q1::Sales" ends up as
+/// `{"Q1": {"Sales": ...}}`. Where a column "Q1" holds a value of its own,
+/// "Q1::Sales" stays a flat key whatever the column order.
+fn write_json(df: &DataFrame, writer: impl Write, options: &WriteOptions) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let columns: Vec<Series> = df.get_columns().iter().map(|c| c.as_materialized_series().rechunk()).collect();
+    let names: HashSet<&str> = columns.iter().map(|series| series.name().as_str()).collect();
+    let nested: Vec<bool> = columns
+        .iter()
+        .map(|series| {
+            let name = series.name().as_str();
+            let mut branches = name.match_indices(NESTED_SEPARATOR).map(|(at, _)| &name[..at]);
+            options.json_nested && !branches.any(|branch| names.contains(branch))
+        })
+        .collect();
+    let mut records = Vec::with_capacity(df.height());
+    for row in 0..df.height() {
+        let mut record = serde_json::Map::new();
+        for (series, &nested) in columns.iter().zip(&nested) {
+            let value = json_value(series.get(row)?);
+            let name = series.name().as_str();
+            if nested {
+                insert_nested(&mut record, name, value);
+            } else {
+                record.insert(name.to_string(), value);
+            }
+        }
+        records.push(serde_json::Value::Object(record));
+    }
+    let writer = std::io::BufWriter::new(writer);
+    match options.json_pretty {
+        true => serde_json::to_writer_pretty(writer, &records)?,
+        false => serde_json::to_writer(writer, &records)?,
+    }
+    Ok(())
+}
+
+/// Writes a GitHub-flavored Markdown table, with columns padded to line up.
+///
+/// Numeric columns are right-aligned and nulls are blank. "|" is escaped and
+/// line breaks become `<br>` so every row stays on one line. Past
+/// `markdown_max_rows` rows or `markdown_max_cols` columns the table is cut
+/// off, with a note saying how much is shown.
+fn write_markdown(df: &DataFrame, writer: impl Write, options: &WriteOptions) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let height = options.markdown_max_rows.map_or(df.height(), |max| max.min(df.height()));
+    let width = options.markdown_max_cols.map_or(df.width(), |max| max.min(df.width()));
+    let escape = |text: &str| text.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>");
+    let mut cells: Vec<Vec<String>> = Vec::with_capacity(width);
+    for column in &df.get_columns()[..width] {
+        let series = column.as_materialized_series().rechunk();
+        let mut texts = vec![escape(series.name())];
+        for row in 0..height {
+            texts.push(match series.get(row)? {
+                AnyValue::Null => String::new(),
+                AnyValue::String(text) => escape(text),
+                value => escape(&value.to_string()),
+            });
+        }
+        cells.push(texts);
+    }
+    let widths: Vec<usize> = cells
+        .iter()
+        .map(|texts| texts.iter().map(|text| text.chars().count()).max().unwrap_or(0).max(3))
+        .collect();
+    let numeric: Vec<bool> = df.get_columns()[..width].iter().map(|c| c.dtype().is_primitive_numeric()).collect();
+
+    let mut text = String::new();
+    for line in 0..=height + 1 {
+        text.push('|');
+        for (col_idx, texts) in cells.iter().enumerate() {
+            let pad = widths[col_idx];
+            let cell = match line {
+                1 if numeric[col_idx] => format!("{}:", "-".repeat(pad - 1)),
+                1 => "-".repeat(pad),
+                0 => format!("{:<pad$}", texts[0]),
+                _ if numeric[col_idx] => format!("{:>pad$}", texts[line - 1]),
+                _ => format!("{:<pad$}", texts[line - 1]),
+            };
+            text.push_str(&format!(" {} |", cell));
+        }
+        text.push('\n');
+    }
+    if height < df.height() || width < df.width() {
+        text.push_str(&format!("\n_Showing {} of {} rows and {} of {} columns._\n", height, df.height(), width, df.width()));
+    }
+    std::io::BufWriter::new(writer).write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// Writes per-column statistics of `df` as JSON, for `--profile-out`.
+///
+/// Every column gets its type, null and distinct counts, min and max where
+/// the type has an order, and up to five distinct sample values. Nulls are
+/// not counted as a distinct value. With `atomic` the profile is written to
+/// [`temp_path`] first and moved over `path` once complete.
+pub fn write_profile(df: &DataFrame, path: &str, atomic: bool) -> Result<(), Box<dyn Error>> {
+    let mut columns = Vec::with_capacity(df.width());
+    for column in df.get_columns() {
+        let series = column.as_materialized_series();
+        let reduce = |scalar: PolarsResult<Scalar>| scalar.map_or(serde_json::Value::Null, |s| json_value(s.value().clone()));
+        let values = series.drop_nulls();
+        let samples: Vec<serde_json::Value> = match values.unique_stable() {
+            Ok(unique) => unique.head(Some(5)).iter().map(json_value).collect(),
+            Err(_) => Vec::new(),
+        };
+        columns.push(serde_json::json!({
+            "name": series.name().as_str(),
+            "dtype": series.dtype().to_string(),
+            "null_count": series.null_count(),
+            "distinct_count": values.n_unique().ok(),
+            "min": reduce(series.min_reduce()),
+            "max": reduce(series.max_reduce()),
+            "samples": samples,
+        }));
+    }
+    let profile = serde_json::json!({ "rows": df.height(), "columns": columns });
+    let target = if atomic { temp_path(path) } else { path.to_string() };
+    let file = File::create(&target).map_err(|e| ExcelReaderError::output_write(path, e))?;
+    let temp = TempFile(atomic.then(|| target.clone()));
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), &profile).map_err(|e| ExcelReaderError::output_write(path, e))?;
+    match temp.keep() {
+        Some(temp) => replace_with_temp(&temp, path),
+        None => Ok(()),
+    }
+}
+
+
+fn json_value(value: AnyValue) -> serde_json::Value {
+    match value {
+        AnyValue::Null => serde_json::Value::Null,
+        AnyValue::Boolean(b) => b.into(),
+        AnyValue::String(s) => s.into(),
+        AnyValue::StringOwned(s) => s.as_str().into(),
+        // A float would lose digits past the 17th, the text keeps them all
+        AnyValue::Decimal(v, scale) => format_decimal(v, scale).into(),
+        value if value.dtype().is_integer() => match value.extract::<i64>() {
+            Some(i) => i.into(),
+            None => value.extract::<u64>().into(),
+        },
+        // NaN and infinity have no JSON form and become null
+        value if value.dtype().is_float() => value.extract::<f64>().into(),
+        value => value.to_string().into(),
+    }
+}
+
+fn insert_nested(record: &mut serde_json::Map<String, serde_json::Value>, name: &str, value: serde_json::Value) {
+    let mut parts: Vec<&str> = name.split(NESTED_SEPARATOR).collect();
+    let leaf = parts.pop().unwrap_or(name);
+    let mut target = &mut *record;
+    for part in parts {
+        let slot = target.entry(part).or_insert_with(|| serde_json::Value::Object(Default::default()));
+        match slot {
+            serde_json::Value::Object(inner) => target = inner,
+            _ => {
+                record.insert(name.to_string(), value);
+                return;
+            }
+        }
+    }
+    target.insert(leaf.to_string(), value);
+}
+
+
+/// A DataFrame as a workbook of one worksheet with a bold header row.
+///
+/// Numbers, booleans and temporal values become native Excel cells, dates
+/// and times with a matching number format. Nulls are left as empty cells.
+/// Excel keeps 15 significant digits, so a decimal with more is written as
+/// text with a warning rather than rounded.
+fn xlsx_workbook(df: &DataFrame, sheet_name: &str) -> Result<Workbook, Box<dyn Error + Send + Sync>> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(sheet_name)?;
+    let header = Format::new().set_bold();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+    let datetime = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+    let time = Format::new().set_num_format("hh:mm:ss");
+    let duration = Format::new().set_num_format("[h]:mm:ss");
+
+    for (col_idx, column) in df.get_columns().iter().enumerate() {
+        let col = u16::try_from(col_idx)?;
+        sheet.write_string_with_format(0, col, column.name().as_str(), &header)?;
+        let series = column.as_materialized_series().rechunk();
+        let mut decimals_as_text = 0;
+        for (row_idx, value) in series.iter().enumerate() {
+            let row = u32::try_from(row_idx + 1)?;
+            match value {
+                AnyValue::Null => {}
+                AnyValue::Boolean(b) => {
+                    sheet.write_boolean(row, col, b)?;
+                }
+                AnyValue::String(s) => {
+                    sheet.write_string(row, col, s)?;
+                }
+                AnyValue::Date(days) => {
+                    let value = chrono::DateTime::UNIX_EPOCH.date_naive() + chrono::Duration::days(days as i64);
+                    sheet.write_datetime_with_format(row, col, value, &date)?;
+                }
+                AnyValue::Datetime(v, unit, _) => {
+                    let value = chrono::DateTime::from_timestamp_micros(to_micros(v, unit))
+                        .ok_or("timestamp out of range")?
+                        .naive_utc();
+                    sheet.write_datetime_with_format(row, col, value, &datetime)?;
+                }
+                AnyValue::Time(ns) => {
+                    let value = chrono::NaiveTime::from_num_seconds_from_midnight_opt(
+                        (ns / 1_000_000_000) as u32,
+                        (ns % 1_000_000_000) as u32,
+                    )
+                    .ok_or("time out of range")?;
+                    sheet.write_datetime_with_format(row, col, value, &time)?;
+                }
+                AnyValue::Duration(v, unit) => {
+                    let days = to_micros(v, unit) as f64 / 86_400_000_000.0;
+                    sheet.write_number_with_format(row, col, days, &duration)?;
+                }
+                AnyValue::Decimal(v, scale) => {
+                    let text = format_decimal(v, scale);
+                    if significant_digits(v) <= 15 {
+                        sheet.write_number(row, col, text.parse::<f64>()?)?;
+                    } else {
+                        sheet.write_string(row, col, text)?;
+                        decimals_as_text += 1;
+                    }
+                }
+                value if value.dtype().is_primitive_numeric() => {
+                    sheet.write_number(row, col, value.extract::<f64>().unwrap_or(f64::NAN))?;
+                }
+                value => {
+                    sheet.write_string(row, col, value.to_string())?;
+                }
+            }
+        }
+        if decimals_as_text > 0 {
+            eprintln!(
+                "Warning: {} values of '{}' have more than 15 significant digits and were written as text",
+                decimals_as_text,
+                column.name(),
+            );
+        }
+    }
+    Ok(workbook)
+}
+
+fn significant_digits(value: i128) -> usize {
+    let mut value = value.unsigned_abs();
+    while value != 0 && value.is_multiple_of(10) {
+        value /= 10;
+    }
+    value.to_string().len()
+}
+
+fn to_micros(value: i64, unit: TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Nanoseconds => value / 1_000,
+        TimeUnit::Microseconds => value,
+        TimeUnit::Milliseconds => value * 1_000,
+    }
+}
+
+
+enum BatchedSink {
+    Csv(Box<CsvBatchedWriter<File>>),
+    Parquet(Box<ParquetBatchedWriter<File>>),
+}
+
+/// Writes a DataFrame to disk one chunk at a time.
+///
+/// CSV chunks are appended after a single header line, Parquet chunks become
+/// separate row groups. Every chunk must share the schema given to `create`.
+/// With `atomic`, a writer dropped before `finish` succeeds removes its
+/// temporary file. The schema sidecar, if asked for, is written by `finish`.
+pub struct ChunkedWriter {
+    path: String,
+    /// The schema for `--write-schema-sidecar`, and whether to write it atomically
+    sidecar: Option<(Schema, bool)>,
+    sink: BatchedSink,
+    /// With `atomic`, the file written until `finish` renames it to `path`.
+    /// It comes after `sink`, so the file is closed before it is removed.
+    temp: TempFile,
+}
+
+/// A temporary output file, removed when dropped unless `keep` took it.
+struct TempFile(Option<String>);
+
+impl TempFile {
+    fn keep(mut self) -> Option<String> {
+        self.0.take()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(path) = &self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl ChunkedWriter {
+    pub fn create(path: &str, schema: &Schema, options: &WriteOptions) -> Result<Self, Box<dyn Error>> {
+        let atomic = options.atomic;
+        let format = OutputFormat::from_path(path)?;
+        if is_object_store_url(path) {
+            return Err(format!("--chunksize writes local files only, '{}' is an object store URL", path).into());
+        }
+        // Refuse before anything at the path is created or truncated
+        let parquet = match format {
+            OutputFormat::Csv => false,
+            OutputFormat::Parquet => true,
+            OutputFormat::Xlsx | OutputFormat::Json | OutputFormat::Markdown => {
+                return Err(format!("--chunksize cannot write '{}', use .csv or .parquet", path).into());
+            }
+        };
+        let temp = atomic.then(|| temp_path(path));
+        let file = File::create(temp.as_deref().unwrap_or(path)).map_err(|e| ExcelReaderError::output_write(path, e))?;
+        let temp = TempFile(temp);
+        let sink = if parquet {
+            ParquetWriter::new(file).batched(schema).map(|w| BatchedSink::Parquet(Box::new(w)))
+        } else {
+            CsvWriter::new(file).batched(schema).map(|w| BatchedSink::Csv(Box::new(w)))
+        }
+        .map_err(|e| ExcelReaderError::output_write(path, e))?;
+        let sidecar = options.schema_sidecar.then(|| (schema.clone(), atomic));
+        Ok(ChunkedWriter { path: path.to_string(), sidecar, sink, temp })
+    }
+
+    pub fn write_chunk(&mut self, df: &DataFrame) -> Result<(), Box<dyn Error>> {
+        let written = match &mut self.sink {
+            BatchedSink::Csv(writer) => writer.write_batch(df),
+            BatchedSink::Parquet(writer) => writer.write_batch(df),
+        };
+        written.map_err(|e| ExcelReaderError::output_write(&self.path, e))?;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        let ChunkedWriter { path, sidecar, sink, temp } = self;
+        let finished = match sink {
+            BatchedSink::Csv(mut writer) => writer.finish(),
+            BatchedSink::Parquet(writer) => writer.finish().map(|_| ()),
+        };
+        finished.map_err(|e| ExcelReaderError::output_write(&path, e))?;
+        if let Some(temp) = temp.keep() {
+            replace_with_temp(&temp, &path)?;
+        }
+        match sidecar {
+            Some((schema, atomic)) => write_schema_sidecar(&path, &schema, atomic),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_schema_sidecar_next_to_csv() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut df = df!("Id" => [1i64, 2], "Region" => ["North", "South"])?;
+        let options = WriteOptions { schema_sidecar: true, atomic: true, ..Default::default() };
+        let csv = dir.path().join("out.csv").to_str().unwrap().to_string();
+        write_dataframe(&mut df, &csv, &options)?;
+        let sidecar: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(schema_sidecar_path(&csv))?)?;
+        assert_eq!(sidecar["columns"], serde_json::json!([{ "name": "Id", "dtype": "i64" }, { "name": "Region", "dtype": "str" }]));
+        let parquet = dir.path().join("out.parquet").to_str().unwrap().to_string();
+        write_dataframe(&mut df, &parquet, &options)?;
+        assert!(!std::path::Path::new(&schema_sidecar_path(&parquet)).exists());
+
+        // The sidecar follows the data, so a failed write leaves none
+        let failed = dir.path().join("failed.csv").to_str().unwrap().to_string();
+        let mut nested = df!("Id" => [Series::new("a".into(), [1i64, 2])])?;
+        assert!(write_dataframe(&mut nested, &failed, &options).is_err());
+        assert!(!std::path::Path::new(&schema_sidecar_path(&failed)).exists());
+        let chunked = dir.path().join("chunked.csv").to_str().unwrap().to_string();
+        let mut writer = ChunkedWriter::create(&chunked, df.schema(), &options)?;
+        writer.write_chunk(&df)?;
+        assert!(!std::path::Path::new(&schema_sidecar_path(&chunked)).exists());
+        writer.finish()?;
+        assert!(std::path::Path::new(&schema_sidecar_path(&chunked)).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn atomic_writes_leave_no_temp_file_behind() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let csv = dir.path().join("out.csv").to_str().unwrap().to_string();
+        let temp = temp_path(&csv);
+        let options = WriteOptions { atomic: true, ..Default::default() };
+        write_dataframe(&mut df!("Id" => [1i64, 2])?, &csv, &options)?;
+        assert_eq!(std::fs::read_to_string(&csv)?, "Id\n1\n2\n");
+        assert!(!std::path::Path::new(&temp).exists());
+
+        // CSV has no list columns, so these writes fail after the temporary file is created
+        let mut nested = df!("Id" => [Series::new("a".into(), [1i64, 2])])?;
+        assert!(write_dataframe(&mut nested, &csv, &options).is_err());
+        assert_eq!(std::fs::read_to_string(&csv)?, "Id\n1\n2\n");
+        assert!(!std::path::Path::new(&temp).exists());
+
+        let other = dir.path().join("chunked.csv").to_str().unwrap().to_string();
+        let mut writer = ChunkedWriter::create(&other, nested.schema(), &options)?;
+        assert!(std::path::Path::new(&temp_path(&other)).exists());
+        assert!(writer.write_chunk(&nested).is_err());
+        drop(writer);
+        assert!(!std::path::Path::new(&temp_path(&other)).exists());
+        assert!(!std::path::Path::new(&other).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn json_keeps_every_decimal_digit() -> Result<(), Box<dyn Error>> {
+        let values = [Some(12345678901234567890123456789i128), Some(-5), None];
+        let amount = Int128Chunked::from_iter_options("amount".into(), values.into_iter()).into_decimal(Some(38), 10)?;
+        let mut df = DataFrame::new(vec![amount.into_series().into_column()])?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("out.json");
+        let path = path.to_str().unwrap();
+        write_dataframe(&mut df, path, &WriteOptions::default())?;
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let expected = serde_json::json!([
+            { "amount": "1234567890123456789.0123456789" },
+            { "amount": "-0.0000000005" },
+            { "amount": null },
+        ]);
+        assert_eq!(written, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn xlsx_cells_read_back_with_their_types() -> Result<(), Box<dyn Error>> {
+        use calamine::{Data, Reader};
+        let decimals = [Some(1250i128), Some(12345678901234567890)];
+        let amount = Int128Chunked::from_iter_options("amount".into(), decimals.into_iter()).into_decimal(Some(38), 2)?;
+        let mut df = df!(
+            "id" => [1i64, 2],
+            "ratio" => [0.5, -1.25],
+            "ok" => [true, false],
+            "name" => ["a", "b"],
+            "day" => [chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(); 2],
+            "at" => [chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(6, 20, 0).unwrap(); 2],
+            "time" => [chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap(); 2],
+        )?;
+        df.with_column(Series::new("took".into(), [90i64 * 60_000, 36 * 3_600_000]).cast(&DataType::Duration(TimeUnit::Milliseconds))?)?;
+        df.with_column(amount.into_series())?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("out.xlsx");
+        write_dataframe(&mut df, path.to_str().unwrap(), &WriteOptions { sheet_name: Some("Report"), ..Default::default() })?;
+
+        let mut book = calamine::open_workbook_auto(&path)?;
+        assert_eq!(book.sheet_names(), ["Report"]);
+        let range = book.worksheet_range("Report")?;
+        let row: Vec<&Data> = (0..df.width() as u32).map(|col| range.get_value((1, col)).unwrap()).collect();
+        assert_eq!(row[0], &Data::Float(1.0));
+        assert_eq!(row[1], &Data::Float(0.5));
+        assert_eq!(row[2], &Data::Bool(true));
+        assert_eq!(row[3], &Data::String("a".into()));
+        let kinds: Vec<Option<(f64, bool)>> = row[4..8]
+            .iter()
+            .map(|cell| match cell {
+                Data::DateTime(value) => Some((value.as_f64(), value.is_duration())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kinds, [
+            Some((45413.0, false)),
+            Some((45413.0 + 380.0 / 1440.0, false)),
+            Some((0.75, false)),
+            Some((90.0 / 1440.0, true)),
+        ]);
+        assert_eq!(row[8], &Data::Float(12.5));
+        // Past 15 significant digits Excel would round, the text keeps every digit
+        assert_eq!(range.get_value((2, 8)), Some(&Data::String("123456789012345678.90".into())));
+        Ok(())
+    }
+
+    #[test]
+    fn nested_json_splits_headers_into_objects() -> Result<(), Box<dyn Error>> {
+        // Names as --header-collapse nested builds them from two header rows
+        let mut df = df!(
+            "Region" => ["North"],
+            "Q1::Sales" => [10],
+            "Q1::Units" => [2],
+            "Q2::Sales" => [20],
+            "Q2" => ["closed"],
+        )?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("out.json");
+        let path = path.to_str().unwrap();
+        let options = WriteOptions { json_nested: true, json_pretty: true, ..Default::default() };
+        write_dataframe(&mut df, path, &options)?;
+        // "Q2" has a value of its own, so "Q2::Sales" stays flat
+        let expected = r#"[
+  {
+    "Region": "North",
+    "Q1": {
+      "Sales": 10,
+      "Units": 2
+    },
+    "Q2::Sales": 20,
+    "Q2": "closed"
+  }
+]"#;
+        assert_eq!(std::fs::read_to_string(path)?, expected);
+
+        write_dataframe(&mut df, path, &WriteOptions { json_nested: true, ..Default::default() })?;
+        assert!(!std::fs::read_to_string(path)?.contains('\n'));
+        Ok(())
+    }
+
+    #[test]
+    fn profile_describes_each_column() -> Result<(), Box<dyn Error>> {
+        let df = df!(
+            "id" => [Some(3i64), Some(1), None, Some(3)],
+            "name" => [Some("b"), Some("a"), Some("b"), None],
+            "ok" => [Some(true), None, None, None],
+        )?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("profile.json");
+        let path = path.to_str().unwrap();
+        write_profile(&df, path, true)?;
+        assert!(!std::path::Path::new(&temp_path(path)).exists());
+        let profile: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let expected = serde_json::json!({
+            "rows": 4,
+            "columns": [
+                { "name": "id", "dtype": "i64", "null_count": 1, "distinct_count": 2, "min": 1, "max": 3, "samples": [3, 1] },
+                { "name": "name", "dtype": "str", "null_count": 1, "distinct_count": 2, "min": "a", "max": "b", "samples": ["b", "a"] },
+                { "name": "ok", "dtype": "bool", "null_count": 3, "distinct_count": 1, "min": true, "max": true, "samples": [true] },
+            ],
+        });
+        assert_eq!(profile, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn chunked_writer_leaves_unsupported_outputs_untouched() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let schema = Schema::from_iter([Field::new("Id".into(), DataType::Int64)]);
+        for (name, atomic) in [("out.xlsx", false), ("out.json", true), ("out.md", false)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, "previous")?;
+            let path = path.to_str().unwrap();
+            assert!(ChunkedWriter::create(path, &schema, &WriteOptions { atomic, ..Default::default() }).is_err());
+            assert_eq!(std::fs::read_to_string(path)?, "previous");
+            assert!(!std::path::Path::new(&temp_path(path)).exists());
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "aws"))]
+    fn object_store_output_needs_its_feature() -> Result<(), Box<dyn Error>> {
+        let mut df = df!("a" => [1])?;
+        assert!(is_object_store_url("s3://bucket/out.parquet"));
+        assert!(!is_object_store_url("out.parquet"));
+        let error = write_dataframe(&mut df, "s3://bucket/out.parquet", &WriteOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("'aws' feature"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn output_template_makes_substituted_names_safe() -> Result<(), Box<dyn Error>> {
+        let expanded = expand_template("out/{stem}-{sheet}.csv", "data/report.v2.xlsx", " Q1/Q2: \"final\"  total ")?;
+        assert_eq!(expanded, "out/report.v2-Q1_Q2_ _final_ total.csv");
+        assert_eq!(expand_template("{sheet}.csv", "a.xlsx", "..")?, "_.csv");
+        assert!(expand_template("{sheet", "a.xlsx", "Data").is_err());
+        assert!(expand_template("{month}.csv", "a.xlsx", "Data").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn dry_output_lists_the_partition_files_written() -> Result<(), Box<dyn Error>> {
+        let mut df = df!(
+            "year" => [2024, 2024, 2025],
+            "region" => [Some("North"), Some("a/b c"), None],
+            "sales" => [1.0, 2.0, 3.0],
+        )?;
+        let dir = tempfile::tempdir()?;
+        let dataset = dir.path().join("dataset");
+        let dataset = dataset.to_str().unwrap();
+        let by = ["year".to_string(), "region".to_string()];
+        let listed = partition_files(&df, dataset, &by)?;
+        write_partitioned(&mut df, dataset, &by, false)?;
+        let mut written: Vec<String> = glob::glob(&format!("{}/**/*.parquet", dataset))?
+            .map(|path| path.map(|path| path.to_string_lossy().into_owned()))
+            .collect::<Result<_, _>>()?;
+        written.sort();
+        assert_eq!(listed, written);
+        assert_eq!(listed.len(), 3);
+        assert!(listed[0].ends_with("year=2024/region=North/00000000.parquet"), "{:?}", listed);
+        // A second run into the same directory would mix old and new partitions
+        assert!(write_partitioned(&mut df, dataset, &by, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn writes_markdown_tables() -> Result<(), Box<dyn Error>> {
+        let mut df = df!("name|alias" => [Some("a\nb"), None, Some("c")], "n" => [1, 20, 3])?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("table.md");
+        let path = path.to_str().unwrap();
+        write_dataframe(&mut df, path, &WriteOptions { markdown_max_rows: Some(2), ..Default::default() })?;
+        let expected = "\
+            | name\\|alias | n   |\n\
+            | ----------- | --: |\n\
+            | a<br>b      |   1 |\n\
+            |             |  20 |\n\
+            \n_Showing 2 of 3 rows and 2 of 2 columns._\n";
+        assert_eq!(std::fs::read_to_string(path)?, expected);
+        Ok(())
+    }
+}
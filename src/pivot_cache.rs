@@ -0,0 +1,157 @@
+use calamine::Data;
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::error::Error;
+
+use crate::notes::{attribute, open_package, read_part, relationships};
+
+
+const CACHE_DEFINITION_REL: &str = "/relationships/pivotCacheDefinition";
+const CACHE_RECORDS_REL: &str = "/relationships/pivotCacheRecords";
+
+/// A cache field's name and the shared items its records index into.
+type CacheField = (String, Vec<Data>);
+
+
+/// The source records a pivot table keeps cached in the workbook.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PivotCache {
+    /// Names of the cached source columns
+    pub fields: Vec<String>,
+    pub records: Vec<Vec<Data>>,
+}
+
+
+/// Reads the cached source data of the pivot table called `name`.
+///
+/// calamine doesn't read pivot tables, so this follows the package
+/// relationships from the pivot table to its cache definition, for the
+/// column names and shared values, and on to the cache records. Text and
+/// dates come back as they are stored, errors as their text, e.g. "#N/A".
+pub fn read_pivot_cache(path: &str, name: &str) -> Result<PivotCache, Box<dyn Error>> {
+    let mut zip = open_package(path)?;
+    let mut parts: Vec<String> = zip
+        .file_names()
+        .filter(|part| part.starts_with("xl/pivotTables/") && part.ends_with(".xml"))
+        .map(|part| part.to_string())
+        .collect();
+    if parts.is_empty() {
+        return Err(format!("'{}' has no pivot tables", path).into());
+    }
+    parts.sort();
+
+    let mut tables = Vec::new();
+    for part in parts {
+        tables.push((pivot_table_name(&read_part(&mut zip, &part)?)?, part));
+    }
+    let Some((_, table_part)) = tables.iter().find(|(table, _)| table == name) else {
+        let names: Vec<&str> = tables.iter().map(|(table, _)| table.as_str()).collect();
+        return Err(format!("Pivot table '{}' not found, available pivot tables: [{}]", name, names.join(", ")).into());
+    };
+
+    let definition = relationships(&mut zip, table_part)?
+        .into_iter()
+        .find(|rel| rel.kind.ends_with(CACHE_DEFINITION_REL))
+        .ok_or_else(|| format!("Pivot table '{}' has no pivot cache", name))?
+        .target;
+    let records = relationships(&mut zip, &definition)?
+        .into_iter()
+        .find(|rel| rel.kind.ends_with(CACHE_RECORDS_REL))
+        .ok_or_else(|| format!("Pivot table '{}' has no cached records, the workbook was saved without its source data", name))?
+        .target;
+
+    let fields = parse_cache_fields(&read_part(&mut zip, &definition)?)?;
+    let records = parse_cache_records(&read_part(&mut zip, &records)?, &fields)?;
+    Ok(PivotCache { fields: fields.into_iter().map(|(field, _)| field).collect(), records })
+}
+
+
+/// The `name` of a pivot table part's `<pivotTableDefinition>`.
+fn pivot_table_name(xml: &str) -> Result<String, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"pivotTableDefinition" => {
+                return Ok(attribute(&e, b"name", reader.decoder())?.unwrap_or_default());
+            }
+            Event::Eof => return Err("Pivot table part without a pivotTableDefinition".into()),
+            _ => {}
+        }
+    }
+}
+
+
+/// Each cache field's name with its shared items, which records refer to by index.
+fn parse_cache_fields(xml: &str) -> Result<Vec<CacheField>, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    let mut fields: Vec<CacheField> = Vec::new();
+    // Grouping items of a field also use <s>, only <sharedItems> children are values
+    let mut in_shared = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"cacheField" => {
+                fields.push((attribute(&e, b"name", reader.decoder())?.unwrap_or_default(), Vec::new()));
+            }
+            Event::Start(e) if e.name().as_ref() == b"sharedItems" => in_shared = true,
+            Event::End(e) if e.name().as_ref() == b"sharedItems" => in_shared = false,
+            Event::Start(e) | Event::Empty(e) if in_shared => {
+                if let (Some(value), Some((_, items))) = (cache_value(&e, reader.decoder())?, fields.last_mut()) {
+                    items.push(value);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(fields)
+}
+
+
+/// The cache records, with `<x>` indices looked up in the field's shared items.
+fn parse_cache_records(xml: &str, fields: &[CacheField]) -> Result<Vec<Vec<Data>>, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    let mut records = Vec::new();
+    let mut record: Option<Vec<Data>> = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.name().as_ref() == b"r" => record = Some(Vec::with_capacity(fields.len())),
+            Event::End(e) if e.name().as_ref() == b"r" => records.extend(record.take()),
+            Event::Start(e) | Event::Empty(e) if record.is_some() => {
+                let Some(values) = record.as_mut() else { continue };
+                let value = match e.name().as_ref() {
+                    b"x" => {
+                        let index = attribute(&e, b"v", reader.decoder())?.and_then(|v| v.parse::<usize>().ok());
+                        fields
+                            .get(values.len())
+                            .and_then(|(_, items)| items.get(index?))
+                            .cloned()
+                            .ok_or_else(|| format!("Pivot cache record refers to a missing shared item of column {}", values.len() + 1))?
+                    }
+                    _ => match cache_value(&e, reader.decoder())? {
+                        Some(value) => value,
+                        None => continue,
+                    },
+                };
+                values.push(value);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(records)
+}
+
+
+/// A cached value element: `<s>` text, `<n>` number, `<b>` boolean, `<d>` date, `<e>` error or `<m>` missing.
+fn cache_value(element: &BytesStart, decoder: Decoder) -> Result<Option<Data>, Box<dyn Error>> {
+    let value = attribute(element, b"v", decoder)?.unwrap_or_default();
+    Ok(Some(match element.name().as_ref() {
+        b"s" | b"e" => Data::String(value),
+        b"n" => Data::Float(value.parse().map_err(|_| format!("Invalid number '{}' in pivot cache", value))?),
+        b"b" => Data::Bool(value == "1" || value == "true"),
+        b"d" => Data::DateTimeIso(value),
+        b"m" => Data::Empty,
+        _ => return Ok(None),
+    }))
+}
@@ -0,0 +1,1095 @@
+use calamine::{Data, Range, Reader};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::delimited;
+use crate::display;
+use crate::error::ExcelReaderError;
+use crate::formats::{self, DateSystem};
+use crate::frame::{FrameOptions, process_headers};
+use crate::workbook::{self, find_sheet_name, open_excel};
+
+
+/// Separates the levels of a nested header name, rare in header text unlike "." or "/".
+pub const NESTED_SEPARATOR: &str = "::";
+
+
+/// Worksheet rows together with the collapsed header names.
+pub struct SheetData {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Data>>,
+    /// Index of the first data row in `rows`
+    pub data_start: usize,
+}
+
+impl SheetData {
+    pub fn data_rows(&self) -> &[Vec<Data>] {
+        &self.rows[self.data_start..]
+    }
+}
+
+
+/// What the sheet holds beyond calamine's cells, read from the package when an option needs it.
+#[derive(Debug, Default)]
+pub struct SheetPackage {
+    /// Number formats by 0-based worksheet `(row, col)`, for `--formatted-values`
+    pub formats: Option<HashMap<(u32, u32), String>>,
+    /// Whether serial dates count from 1904, for numbers `--formatted-values` shows as dates
+    pub date1904: bool,
+    /// 0-based worksheet rows hidden in the sheet, for `--visible-only`
+    pub hidden: Option<HashSet<u32>>,
+}
+
+/// Reads the parts of the sheet that `options` need, naming the sheet only then.
+pub fn sheet_package(
+    path: &str,
+    sheet_name: impl FnOnce() -> Result<String, Box<dyn Error>>,
+    options: &FrameOptions,
+) -> Result<SheetPackage, Box<dyn Error>> {
+    if options.formatted_values.is_none() && !options.visible_only {
+        return Ok(SheetPackage::default());
+    }
+    let name = sheet_name()?;
+    let date1904 = match options.date_system {
+        DateSystem::Excel1900 => false,
+        DateSystem::Excel1904 => true,
+        DateSystem::Auto => options.formatted_values.is_some() && display::uses_1904_dates(path)?,
+    };
+    Ok(SheetPackage {
+        formats: options.formatted_values.as_ref().map(|_| display::cell_formats(path, &name)).transpose()?,
+        date1904,
+        hidden: options.visible_only.then(|| display::hidden_rows(path, &name)).transpose()?,
+    })
+}
+
+
+/// With `--header-from-frozen`, the rows frozen at the top of the sheet as header rows.
+///
+/// Frozen rows count from Excel row 1 while header rows count from the
+/// first used row, so frozen rows above it are left out. Without frozen rows
+/// in the range the default header row is used.
+pub fn frozen_header_rows(path: &str, sheet_name: &str, range: &Range<Data>) -> Result<Option<Vec<usize>>, Box<dyn Error>> {
+    let Some(frozen) = display::frozen_rows(path, sheet_name)? else {
+        return Ok(None);
+    };
+    let first_row = range.start().map_or(0, |(row, _)| row);
+    Ok(Some((0..frozen.saturating_sub(first_row) as usize).collect()).filter(|rows: &Vec<usize>| !rows.is_empty()))
+}
+
+
+/// How `--auto-header` finds the header row among the top rows of a sheet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoHeader {
+    /// The first all-text row about as wide as the table, see `filled_header_row`
+    Filled,
+    /// The last all-text row above the typed data, see `type_change_header_row`
+    TypeChange,
+}
+
+/// Top rows `--auto-header` looks through for the header.
+const HEADER_SCAN_ROWS: usize = 20;
+
+/// The header row `mode` finds in `range`, 0-based from the first used row, reported on stderr.
+pub fn auto_header_row(range: &Range<Data>, mode: AutoHeader, sheet_name: &str) -> usize {
+    let row = match mode {
+        AutoHeader::Filled => filled_header_row(range),
+        AutoHeader::TypeChange => type_change_header_row(range),
+    };
+    let first_row = range.start().map_or(0, |(row, _)| row as usize);
+    eprintln!("Using Excel row {} of '{}' as the header", first_row + row + 1, sheet_name);
+    row
+}
+
+/// The header row of a sheet with titles or notes above its table.
+///
+/// It is the first of the top rows that is all text and fills more than
+/// half as many cells as the widest of them, so a one-cell title is passed
+/// over. Without such a row it is the first of the widest rows.
+fn filled_header_row(range: &Range<Data>) -> usize {
+    let filled = |row: &[Data]| row.iter().filter(|cell| **cell != Data::Empty).count();
+    let top: Vec<&[Data]> = range.rows().take(HEADER_SCAN_ROWS).collect();
+    let widest = top.iter().map(|row| filled(row)).max().unwrap_or(0);
+    top.iter()
+        .position(|row| filled(row) * 2 > widest && row.iter().all(|cell| matches!(cell, Data::Empty | Data::String(_))))
+        .or_else(|| top.iter().position(|row| filled(row) == widest))
+        .unwrap_or(0)
+}
+
+/// The header row found where the cell types change from text to numbers or dates.
+///
+/// Data starts at the first row with typed cells whose next row has typed
+/// cells in at least one of the same columns, so a lone "Year: 2024" line
+/// doesn't count. The header is the last all-text row above it, which
+/// passes over titles, notes and the upper rows of a multi-row header.
+/// Numbers written as text, as in .csv input, count as typed. Without such
+/// a change the first row is the header.
+pub fn type_change_header_row(range: &Range<Data>) -> usize {
+    let typed = |cell: &Data| match cell {
+        Data::Int(_) | Data::Float(_) | Data::Bool(_) | Data::DateTime(_) | Data::DateTimeIso(_) | Data::DurationIso(_) => true,
+        Data::String(text) => text.trim().parse::<f64>().is_ok(),
+        Data::Empty | Data::Error(_) => false,
+    };
+    let typed_columns = |row: &[Data]| -> Vec<usize> { (0..row.len()).filter(|&col| typed(&row[col])).collect() };
+    let all_text = |row: &[Data]| row.iter().any(|cell| matches!(cell, Data::String(_))) && typed_columns(row).is_empty();
+
+    // One more row than the header may be on, to see whether the data row's types hold
+    let top: Vec<&[Data]> = range.rows().take(HEADER_SCAN_ROWS + 1).collect();
+    let data_start = (1..top.len()).find(|&i| {
+        let columns = typed_columns(top[i]);
+        !columns.is_empty() && top.get(i + 1).is_none_or(|next| typed_columns(next).iter().any(|col| columns.contains(col)))
+    });
+    data_start
+        .and_then(|start| (0..start).rev().find(|&i| all_text(top[i])))
+        .unwrap_or(0)
+}
+
+
+/// The cells of a worksheet, its header rows and what the options need from its package.
+pub struct WorksheetParts {
+    pub range: Range<Data>,
+    pub header_rows: Option<Vec<usize>>,
+    pub package: SheetPackage,
+}
+
+/// Reads a worksheet's parts, finding its header rows with `--header-from-frozen` or `--auto-header`.
+pub fn worksheet_parts(
+    path: &str,
+    worksheet_name: Option<&str>,
+    header_rows: Option<Vec<usize>>,
+    options: &FrameOptions,
+) -> Result<WorksheetParts, Box<dyn Error>> {
+    let range = get_worksheet_range(path, worksheet_name, options)?;
+    let package = sheet_package(path, || resolve_sheet_name(path, worksheet_name, None, options), options)?;
+    let header_rows = match header_rows {
+        None if options.header_from_frozen => {
+            frozen_header_rows(path, &resolve_sheet_name(path, worksheet_name, None, options)?, &range)?
+        }
+        None if let Some(mode) = options.auto_header => {
+            Some(vec![auto_header_row(&range, mode, &resolve_sheet_name(path, worksheet_name, None, options)?)])
+        }
+        header_rows => header_rows,
+    };
+    Ok(WorksheetParts { range, header_rows, package })
+}
+
+
+/// The name of the sheet that `worksheet_name` or `table` selects, defaulting to the first one.
+pub fn resolve_sheet_name(
+    path: &str,
+    worksheet_name: Option<&str>,
+    table: Option<&str>,
+    options: &FrameOptions,
+) -> Result<String, Box<dyn Error>> {
+    if options.separator.is_some() {
+        // Named like a one-sheet workbook, after the file
+        let stem = std::path::Path::new(path).file_stem().and_then(|stem| stem.to_str());
+        return Ok(stem.unwrap_or(path).to_string());
+    }
+    let mut workbook = open_excel(path)?;
+    if let Some(name) = worksheet_name {
+        return find_sheet_name(&workbook.sheet_names(), name, options.sheet_match);
+    }
+    if let Some(table) = table {
+        workbook.load_tables()?;
+        return workbook
+            .sheet_names()
+            .into_iter()
+            .find(|sheet| workbook.table_names_in_sheet(sheet).iter().any(|name| *name == table))
+            .ok_or_else(|| format!("Table '{}' not found", table).into());
+    }
+    workbook
+        .sheet_names()
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No worksheets found in the workbook".into())
+}
+
+
+/// The cells of a worksheet, or of a .csv or .tsv file read like one.
+pub fn get_worksheet_range(
+    path: &str,
+    worksheet_name: Option<&str>,
+    options: &FrameOptions,
+) -> Result<Range<Data>, Box<dyn Error>> {
+    if let Some(separator) = options.separator {
+        return delimited::read_delimited(path, separator);
+    }
+    let mut workbook = open_excel(path)?;
+    workbook::worksheet_range(&mut workbook, worksheet_name, options.sheet_match, options.formulas)
+}
+
+
+/// The part of `range` that `--range` or `--anchor auto` select, all of it without them.
+pub fn selected_range<'a>(range: &'a Range<Data>, options: &FrameOptions) -> Cow<'a, Range<Data>> {
+    match options.cell_range {
+        Some((start, end)) => Cow::Owned(workbook::slice_range(range, start, end)),
+        None if options.auto_anchor => match workbook::largest_block(range) {
+            Some((start, end)) => Cow::Owned(workbook::slice_range(range, start, end)),
+            None => Cow::Borrowed(range),
+        },
+        None => Cow::Borrowed(range),
+    }
+}
+
+/// Number of rows of the selected range above `--last-row`.
+pub fn selected_height(range: &Range<Data>, options: &FrameOptions) -> usize {
+    match options.last_row {
+        // `last_row` is an Excel row number, the range may start below row 1
+        Some(last_row) => range.height().min(last_row.saturating_sub(range.start().map_or(0, |(row, _)| row)) as usize),
+        None => range.height(),
+    }
+}
+
+/// Copies rows of the selected range: the first `head`, then those in `body`, or all rows without it.
+pub fn selected_rows(range: &Range<Data>, head: usize, body: Option<&std::ops::Range<usize>>, options: &FrameOptions) -> Vec<Vec<Data>> {
+    let height = selected_height(range, options);
+    let mut rows: Vec<Vec<Data>> = match body {
+        Some(body) => range
+            .rows()
+            .take(head.min(height))
+            .chain(range.rows().take(height).skip(body.start).take(body.len()))
+            .map(|row| row.to_vec())
+            .collect(),
+        None => range.rows().take(height).map(|row| row.to_vec()).collect(),
+    };
+    limit_cell_length(&mut rows, options);
+    formats::apply_date_system(&mut rows, options.date_system);
+    rows
+}
+
+
+/// Splits worksheet cells into header names and rows.
+///
+/// `package` has the number formats of the sheet's cells, used for
+/// `--formatted-values`, and its hidden rows, left out with `--visible-only`.
+pub fn sheet_from_range(
+    range: &Range<Data>,
+    header_rows: Option<Vec<usize>>,
+    package: &SheetPackage,
+    options: &FrameOptions,
+) -> Result<SheetData, Box<dyn Error>> {
+    sheet_rows_from_range(&selected_range(range, options), header_rows, package, options, None)
+}
+
+/// Like [`sheet_from_range`] on an already selected range, copying only the data rows in `body`.
+///
+/// `body` counts rows from the top of the range, like header rows do. Chunks
+/// after the first leave out the rows above it, so with `--keep-header-in-data`
+/// or `--header-gap data` only the first chunk has the rows between the headers.
+pub fn sheet_rows_from_range(
+    range: &Range<Data>,
+    header_rows: Option<Vec<usize>>,
+    package: &SheetPackage,
+    options: &FrameOptions,
+    body: Option<std::ops::Range<usize>>,
+) -> Result<SheetData, Box<dyn Error>> {
+    let header_rows = header_rows.unwrap_or(vec![0]);
+    if options.units_row.is_some_and(|units| header_rows.contains(&units)) {
+        return Err("--units-row must not be one of the --header rows".into());
+    }
+    let start = range.start();
+    // Rows down to the last header row are always read, the data rows below them may be skipped
+    let head = header_rows.iter().chain(&options.units_row).max().copied().unwrap_or(0) + 1;
+    let skipped = body.as_ref().map_or(0, |body| body.start.saturating_sub(head));
+    let mut rows = selected_rows(range, head, body.as_ref(), options);
+    let mut head_len = head.min(rows.len());
+    // Check header indices are in bounds
+    for &idx in header_rows.iter().chain(&options.units_row) {
+        if idx >= rows.len() {
+            // Ranges start at the first used cell, which is not necessarily Excel row 1
+            let first_row = start.map_or(1, |(row, _)| row + 1);
+            return Err(ExcelReaderError::HeaderOutOfBounds { index: idx, rows: rows.len(), first_row }.into());
+        }
+    }
+
+    // Collect header rows
+    let mut header_cells: Vec<&[Data]> = header_rows.iter().map(|&i| &rows[i][..]).collect();
+    check_header_widths(&header_rows, &header_cells, options.strict_headers)?;
+    // --header-fill-forward fills the top-most row, which holds the merged category labels
+    let fill_rows = if options.header_fill_forward { &[0][..] } else { &options.ffill_header_rows[..] };
+    let width = header_cells.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut top_down: Vec<usize> = (0..header_rows.len()).collect();
+    top_down.sort_by_key(|&i| header_rows[i]);
+    let filled = fill_rows
+        .iter()
+        .map(|&fill| match top_down.get(fill) {
+            Some(&i) => Ok((i, fill_forward(header_cells[i], width))),
+            None => Err(format!("--ffill-header-rows {} is out of range, there are {} header rows", fill, header_rows.len())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, row) in &filled {
+        header_cells[*i] = row;
+    }
+    // Collapse headers
+    // With --deterministic-unnamed, blank headers are named after their worksheet column
+    let first_col = options.unnamed_by_letter.then(|| start.map_or(0, |(_, col)| col));
+    let mut headers = collapse_multi_headers(&header_cells, options, first_col)?;
+    if let Some(units) = options.units_row {
+        attach_units(&mut headers, &rows[units]);
+    }
+    normalize_unicode(&mut headers, &mut rows, options);
+    coerce_headers_ascii(&mut headers, options);
+    prefix_numeric_headers(&mut headers, options);
+    rename_headers(&mut headers, options)?;
+    if let Some(formats) = &package.formats {
+        let (head_rows, body_rows) = rows.split_at_mut(head_len);
+        apply_display_formats(head_rows, start, &headers, formats, package.date1904, options)?;
+        let body_start = start.map(|(row, col)| (row + (head + skipped) as u32, col));
+        apply_display_formats(body_rows, body_start, &headers, formats, package.date1904, options)?;
+    }
+    if let Some(hidden) = &package.hidden {
+        // Only data rows go, below the header rows so their indices stay put
+        let first_row = start.map_or(0, |(row, _)| row);
+        let mut row_idx = 0;
+        rows.retain(|_| {
+            let sheet_row = first_row + (row_idx + if row_idx < head { 0 } else { skipped }) as u32;
+            let keep = row_idx < head || !hidden.contains(&sheet_row);
+            row_idx += 1;
+            keep
+        });
+    }
+
+    // Data starts after the last header row, or with --keep-header-in-data at the first one
+    // The units row is laid out like one more header row
+    let header_rows: Vec<usize> = header_rows.into_iter().chain(options.units_row).collect();
+    let first_header = header_rows.iter().min().copied().unwrap_or(0);
+    let last_header = header_rows.iter().max().copied().unwrap_or(0);
+    let data_start = if options.keep_header_in_data {
+        first_header
+    } else if options.header_gap_as_data {
+        // Without the header rows, rows between them are the first data rows
+        let mut row_idx = 0;
+        let before = rows.len();
+        rows.retain(|_| {
+            let keep = !header_rows.contains(&row_idx);
+            row_idx += 1;
+            keep
+        });
+        head_len -= before - rows.len();
+        first_header
+    } else {
+        let skipped = (first_header..last_header)
+            .filter(|i| !header_rows.contains(i) && rows[*i].iter().any(|cell| !matches!(cell, Data::Empty)))
+            .count();
+        if skipped > 0 {
+            eprintln!("Warning: {} non-empty rows between header rows are skipped, use --header-gap data to keep them", skipped);
+        }
+        last_header + 1
+    };
+    // Later chunks start at their own rows
+    let data_start = if skipped > 0 { head_len } else { data_start };
+    strip_currency_symbols(&mut rows[data_start..], options);
+    strip_thousands(&mut rows[data_start..], options);
+    let sheet = SheetData { headers, rows, data_start };
+    Ok(if options.coalesce_headers { coalesce_columns(sheet) } else { sheet })
+}
+
+
+/// Replaces number and date cells by the text Excel shows for them, for `--formatted-values`.
+///
+/// Only the listed columns change, or every column when none are listed.
+/// `start` is the worksheet position of `rows[0][0]`, `is_1904` the workbook's date system.
+fn apply_display_formats(
+    rows: &mut [Vec<Data>],
+    start: Option<(u32, u32)>,
+    headers: &[String],
+    formats: &HashMap<(u32, u32), String>,
+    is_1904: bool,
+    options: &FrameOptions,
+) -> Result<(), Box<dyn Error>> {
+    let Some(scope) = &options.formatted_values else { return Ok(()) };
+    let names = process_headers(headers.to_vec());
+    let columns: Vec<usize> = match scope.is_empty() {
+        true => (0..names.len()).collect(),
+        false => scope
+            .iter()
+            .map(|column| {
+                names
+                    .iter()
+                    .position(|name| name == column)
+                    .ok_or_else(|| format!("--formatted-values column '{}' not found", column))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let (first_row, first_col) = start.unwrap_or((0, 0));
+    for (row_idx, row) in rows.iter_mut().enumerate() {
+        for &col in &columns {
+            let Some(cell) = row.get_mut(col) else { continue };
+            let position = (first_row + row_idx as u32, first_col + col as u32);
+            if let Some(text) = formats.get(&position).and_then(|code| display::format_cell(cell, code, is_1904)) {
+                *cell = Data::String(text);
+            }
+        }
+    }
+    Ok(())
+}
+
+
+/// Cuts text cells longer than `--limit-cell-length` down to that many characters, ending in "…".
+///
+/// Returns how many cells were cut.
+pub fn limit_cell_length(rows: &mut [Vec<Data>], options: &FrameOptions) -> usize {
+    let Some(limit) = options.max_cell_length else { return 0 };
+    let mut truncated = 0;
+    for cell in rows.iter_mut().flatten() {
+        if let Data::String(text) = cell {
+            // The byte offset of the first character that no longer fits, if any
+            if let Some((cut, _)) = text.char_indices().nth(limit) {
+                let keep = text[..cut].char_indices().nth(limit - 1).map_or(cut, |(i, _)| i);
+                text.truncate(keep);
+                text.push('…');
+                truncated += 1;
+            }
+        }
+    }
+    if truncated > 0 {
+        eprintln!("Warning: {} cells were longer than {} characters and were truncated", truncated, limit);
+    }
+    truncated
+}
+
+
+/// Rewrites amounts like "$1,234.56" or "€1.234,56" in data rows as plain numbers, for `--strip-currency-symbols`.
+///
+/// Only text cells containing one of the symbols change, and only when the
+/// rest is a number, see `strip_currency`. The result is text like
+/// "1234.56", which `--infer-types` can then make numeric.
+pub fn strip_currency_symbols(rows: &mut [Vec<Data>], options: &FrameOptions) {
+    if options.currency_symbols.is_empty() {
+        return;
+    }
+    for cell in rows.iter_mut().flatten() {
+        if let Data::String(text) = cell
+            && let Some(number) = strip_currency(text, &options.currency_symbols)
+        {
+            *text = number;
+        }
+    }
+}
+
+
+/// Removes currency symbols, spaces and the thousands separator from an amount.
+///
+/// When both "." and "," appear, the last one is the decimal mark. A lone
+/// separator is a thousands separator if exactly three digits follow it
+/// each time ("$1,234", "€1.234"), otherwise the decimal mark ("€1,50").
+pub fn strip_currency(text: &str, symbols: &[String]) -> Option<String> {
+    if !symbols.iter().any(|symbol| text.contains(symbol.as_str())) {
+        return None;
+    }
+    let mut amount = text.to_string();
+    for symbol in symbols {
+        amount = amount.replace(symbol.as_str(), "");
+    }
+    amount.retain(|c| !c.is_whitespace());
+    let groups_of_three = |mark: char| amount.split(mark).skip(1).all(|group| group.len() == 3);
+    let decimal_mark = match (amount.rfind('.'), amount.rfind(',')) {
+        (Some(dot), Some(comma)) => if dot > comma { '.' } else { ',' },
+        (Some(_), None) if amount.matches('.').count() > 1 || groups_of_three('.') => ',',
+        (None, Some(_)) if amount.matches(',').count() > 1 || groups_of_three(',') => '.',
+        (None, Some(_)) => ',',
+        _ => '.',
+    };
+    let thousands = if decimal_mark == '.' { ',' } else { '.' };
+    let number: String = amount
+        .chars()
+        .filter(|&c| c != thousands)
+        .map(|c| if c == decimal_mark { '.' } else { c })
+        .collect();
+    let is_number = number.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+        && number.parse::<f64>().is_ok();
+    is_number.then_some(number)
+}
+
+
+/// Removes the `--strip-thousands` separator from numbers like "1,234,567" in data rows.
+///
+/// A column only changes when every text cell in it is a number with the
+/// separator between groups of three digits, so text columns holding values
+/// like "Smith, John" or "1,5" keep them.
+pub fn strip_thousands(rows: &mut [Vec<Data>], options: &FrameOptions) {
+    let Some(separator) = options.thousands_separator.as_deref().filter(|s| !s.is_empty()) else { return };
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    for col_idx in 0..width {
+        let mut stripped = Vec::new();
+        let is_numeric = rows.iter().enumerate().all(|(row_idx, row)| match row.get(col_idx) {
+            Some(Data::String(text)) if !text.trim().is_empty() => match without_thousands(text, separator) {
+                Some(number) => {
+                    stripped.push((row_idx, number));
+                    true
+                }
+                None => false,
+            },
+            _ => true,
+        });
+        if is_numeric {
+            for (row_idx, number) in stripped {
+                rows[row_idx][col_idx] = Data::String(number);
+            }
+        }
+    }
+}
+
+/// The number `text` holds without thousands separators, or `None` if it isn't one.
+///
+/// Digits before the separator come in groups of three after the first, a
+/// fraction after "." is allowed unless "." is the separator.
+pub fn without_thousands(text: &str, separator: &str) -> Option<String> {
+    let text = text.trim();
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (whole, fraction) = match unsigned.split_once('.') {
+        Some((whole, fraction)) if separator != "." => (whole, Some(fraction)),
+        _ => (unsigned, None),
+    };
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let mut groups = whole.split(separator);
+    let first = groups.next()?;
+    let valid = digits(first)
+        && first.len() <= 3
+        && groups.all(|group| group.len() == 3 && digits(group))
+        && fraction.is_none_or(digits);
+    // Numbers below a thousand have no separator, but still fit the column
+    let valid = valid || (digits(whole) && fraction.is_none_or(digits));
+    valid.then(|| text.replace(separator, ""))
+}
+
+
+/// Brings header names, and with `--normalize-cells` text cells, into the `--normalize-unicode` form.
+pub fn normalize_unicode(headers: &mut [String], rows: &mut [Vec<Data>], options: &FrameOptions) {
+    let Some(form) = options.sheet_match.unicode else { return };
+    for header in headers.iter_mut() {
+        *header = form.normalize(header);
+    }
+    if options.normalize_cells {
+        for cell in rows.iter_mut().flatten() {
+            if let Data::String(text) = cell {
+                *text = form.normalize(text);
+            }
+        }
+    }
+}
+
+
+/// Applies `--coerce-headers-ascii`, before renames and deduplication.
+pub fn coerce_headers_ascii(headers: &mut [String], options: &FrameOptions) {
+    if options.ascii_headers {
+        for header in headers.iter_mut() {
+            *header = transliterate(header);
+        }
+    }
+}
+
+
+/// Latin spellings of letters that don't decompose to ASCII: Russian and
+/// Ukrainian Cyrillic, after common romanization, and a few Latin ligatures.
+const TRANSLITERATIONS: &[(char, &str)] = &[
+    ('а', "a"), ('б', "b"), ('в', "v"), ('г', "g"), ('д', "d"), ('е', "e"), ('ё', "e"), ('ж', "zh"),
+    ('з', "z"), ('и', "i"), ('й', "y"), ('к', "k"), ('л', "l"), ('м', "m"), ('н', "n"), ('о', "o"),
+    ('п', "p"), ('р', "r"), ('с', "s"), ('т', "t"), ('у', "u"), ('ф', "f"), ('х', "kh"), ('ц', "ts"),
+    ('ч', "ch"), ('ш', "sh"), ('щ', "shch"), ('ъ', ""), ('ы', "y"), ('ь', ""), ('э', "e"), ('ю', "yu"),
+    ('я', "ya"), ('є', "ye"), ('і', "i"), ('ї', "yi"), ('ґ', "g"),
+    ('ß', "ss"), ('æ', "ae"), ('œ', "oe"), ('ø', "o"), ('ł', "l"), ('đ', "d"), ('þ', "th"),
+];
+
+/// Spells `text` in ASCII: accents are dropped, e.g. "é" to "e", letters in
+/// the table are romanized keeping their case, and anything else becomes "_".
+pub fn transliterate(text: &str) -> String {
+    let mut ascii = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            ascii.push(c);
+            continue;
+        }
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        if let Some((_, latin)) = TRANSLITERATIONS.iter().find(|(letter, _)| *letter == lower) {
+            let mut letters = latin.chars();
+            if let Some(first) = letters.next() {
+                ascii.push(if c.is_uppercase() { first.to_ascii_uppercase() } else { first });
+            }
+            ascii.extend(letters);
+            continue;
+        }
+        let decomposed: String = std::iter::once(c).nfkd().filter(char::is_ascii).collect();
+        match decomposed.is_empty() {
+            true => ascii.push('_'),
+            false => ascii.push_str(&decomposed),
+        }
+    }
+    ascii
+}
+
+
+/// Applies `--prefix-numeric-headers` to names like "2024", which some consumers reject as identifiers.
+pub fn prefix_numeric_headers(headers: &mut [String], options: &FrameOptions) {
+    let Some(prefix) = &options.numeric_header_prefix else { return };
+    for header in headers.iter_mut().filter(|h| h.starts_with(|c: char| c.is_ascii_digit())) {
+        header.insert_str(0, prefix);
+    }
+}
+
+
+/// Parses a `--rename-regex` "PATTERN=>REPLACEMENT" rule.
+pub fn parse_rename_regex(spec: &str) -> Result<(Regex, String), Box<dyn Error>> {
+    let (pattern, replacement) = spec
+        .split_once("=>")
+        .ok_or_else(|| format!("Invalid rename rule '{}', expected PATTERN=>REPLACEMENT", spec))?;
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid --rename-regex pattern '{}': {}", pattern, e))?;
+    Ok((regex, replacement.to_string()))
+}
+
+
+/// Reads a `--rename-map` JSON file of `{"old name": "new name"}` pairs, in file order.
+pub fn parse_rename_map(path: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read rename map '{}': {}", path, e))?;
+    let map: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid rename map '{}', expected a JSON object: {}", path, e))?;
+    map.into_iter()
+        .map(|(from, to)| match to {
+            serde_json::Value::String(to) => Ok((from, to)),
+            _ => Err(format!("Rename map '{}' gives '{}' a value that is not a string", path, from).into()),
+        })
+        .collect()
+}
+
+/// Applies the `--rename-regex` rules in order to every header name, then the `--rename-map` entries.
+///
+/// Replacements may refer to groups as `$1` or `${name}`. Map entries match
+/// whole names and rename each header at most once, so a map can swap two
+/// names. This runs before deduplication, so names that become equal still
+/// end up unique. Map entries that match no header are a warning, or an
+/// error with `--rename-strict`.
+pub fn rename_headers(headers: &mut [String], options: &FrameOptions) -> Result<(), Box<dyn Error>> {
+    for (regex, replacement) in &options.header_renames {
+        for header in headers.iter_mut() {
+            if let Cow::Owned(renamed) = regex.replace_all(header, replacement.as_str()) {
+                *header = renamed;
+            }
+        }
+    }
+    let mut used = vec![false; options.header_map.len()];
+    for header in headers.iter_mut() {
+        if let Some(i) = options.header_map.iter().position(|(from, _)| from == header) {
+            used[i] = true;
+            header.clone_from(&options.header_map[i].1);
+        }
+    }
+    let missing: Vec<&str> = options
+        .header_map
+        .iter()
+        .zip(&used)
+        .filter(|(_, used)| !**used)
+        .map(|((from, _), _)| from.as_str())
+        .collect();
+    if !missing.is_empty() {
+        let message = format!("--rename-map names columns that are not in the sheet: [{}]", missing.join(", "));
+        if options.rename_strict {
+            return Err(message.into());
+        }
+        eprintln!("Warning: {}", message);
+    }
+    Ok(())
+}
+
+
+/// Merges columns that share a header name into the first of them.
+///
+/// Each merged cell takes the first non-empty value among the columns with
+/// that name, left to right.
+pub fn coalesce_columns(sheet: SheetData) -> SheetData {
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+    for (col_idx, header) in sheet.headers.iter().enumerate() {
+        match groups.iter_mut().find(|(name, _)| name == header) {
+            Some((_, indices)) => indices.push(col_idx),
+            None => groups.push((header.clone(), vec![col_idx])),
+        }
+    }
+    if groups.len() == sheet.headers.len() {
+        return sheet;
+    }
+
+    let is_blank = |cell: &Data| match cell {
+        Data::Empty => true,
+        Data::String(s) => s.trim().is_empty(),
+        _ => false,
+    };
+    let rows = sheet
+        .rows
+        .into_iter()
+        .map(|row| {
+            groups
+                .iter()
+                .map(|(_, indices)| {
+                    indices
+                        .iter()
+                        .filter_map(|&i| row.get(i))
+                        .find(|cell| !is_blank(cell))
+                        .cloned()
+                        .unwrap_or(Data::Empty)
+                })
+                .collect()
+        })
+        .collect();
+    let headers = groups.into_iter().map(|(name, _)| name).collect();
+    SheetData { headers, rows, data_start: sheet.data_start }
+}
+
+
+/// Warns, or fails when `strict`, if header rows end at different columns.
+///
+/// A row's width is measured up to its last non-empty cell, so a short row
+/// shows up even though every row of a range has the same length.
+pub fn check_header_widths(header_rows: &[usize], header_cells: &[&[Data]], strict: bool) -> Result<(), Box<dyn Error>> {
+    let widths: Vec<usize> = header_cells
+        .iter()
+        .map(|row| {
+            row.iter()
+                .rposition(|cell| !matches!(cell, Data::Empty) && !cell.to_string().trim().is_empty())
+                .map_or(0, |last| last + 1)
+        })
+        .collect();
+    if widths.windows(2).all(|pair| pair[0] == pair[1]) {
+        return Ok(());
+    }
+
+    let report = header_rows
+        .iter()
+        .zip(&widths)
+        .map(|(row, width)| format!("row {}: {} columns", row, width))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if strict {
+        return Err(format!("Header rows have different widths ({})", report).into());
+    }
+    eprintln!("Warning: header rows have different widths ({})", report);
+    Ok(())
+}
+
+
+/// How the non-empty parts of a multi-row header become one name.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HeaderCollapse {
+    /// All parts, top to bottom, separated by spaces
+    #[default]
+    Join,
+    /// The top-most part only
+    First,
+    /// The bottom-most, i.e. most specific, part only
+    Last,
+    /// All parts separated by "::", which .json output turns into nested objects
+    Nested,
+}
+
+
+/// Builds one name per column from the header rows.
+///
+/// Columns without any header text become "Unnamed_{index}", or
+/// "Unnamed_{letter}" when `first_col`, the worksheet column of index 0, is
+/// given. The prefix and the separator of joined parts come from `options`,
+/// both may be empty.
+pub fn collapse_multi_headers(
+    header_cells: &Vec<&[Data]>,
+    options: &FrameOptions,
+    first_col: Option<u32>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if header_cells.is_empty() {
+        return Err("Empty header cells".into());
+    }
+    let prefix = options.unnamed_prefix.as_deref().unwrap_or("Unnamed_");
+    let separator = options.header_separator.as_deref().unwrap_or(" ");
+    let cols = header_cells.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut collapsed = Vec::with_capacity(cols);
+    for col_idx in 0..cols {
+        let parts: Vec<String> = header_cells.iter()
+            .map(|row| row.get(col_idx).map(|d| d.to_string()).unwrap_or_default())
+            // Every name starts with an empty prefix, so only a real one marks a generated name
+            .filter(|part| (prefix.is_empty() || !part.starts_with(prefix)) && !part.trim().is_empty())
+            .collect();
+
+        let name = match options.header_collapse {
+            HeaderCollapse::Join => (!parts.is_empty()).then(|| parts.join(separator)),
+            HeaderCollapse::First => parts.first().cloned(),
+            HeaderCollapse::Last => parts.last().cloned(),
+            HeaderCollapse::Nested => (!parts.is_empty()).then(|| parts.join(NESTED_SEPARATOR)),
+        };
+        // With an empty prefix the index or letter alone still tells the columns apart
+        collapsed.push(name.unwrap_or_else(|| match first_col {
+            Some(first_col) => format!("{}{}", prefix, workbook::column_letter(first_col + col_idx as u32)),
+            None => format!("{}{}", prefix, col_idx),
+        }));
+    }
+    Ok(collapsed)
+}
+
+
+/// Appends each column's `--units-row` cell to its name, e.g. "Temperature (°C)".
+///
+/// Columns with an empty unit cell keep their name.
+fn attach_units(headers: &mut [String], units: &[Data]) {
+    for (header, unit) in headers.iter_mut().zip(units) {
+        let unit = unit.to_string();
+        if !unit.trim().is_empty() {
+            *header = format!("{} ({})", header, unit.trim());
+        }
+    }
+}
+
+
+/// Copies each label of a header row into the blank cells after it, up to `width` cells.
+///
+/// Blanks before the first label stay blank.
+fn fill_forward(row: &[Data], width: usize) -> Vec<Data> {
+    let mut label: Option<&Data> = None;
+    (0..width)
+        .map(|col_idx| match row.get(col_idx) {
+            Some(cell) if !cell.to_string().trim().is_empty() => {
+                label = Some(cell);
+                cell.clone()
+            }
+            _ => label.cloned().unwrap_or(Data::Empty),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_regex_runs_before_deduplication() -> Result<(), Box<dyn Error>> {
+        let options = FrameOptions {
+            header_renames: vec![
+                parse_rename_regex("^col_=>")?,
+                parse_rename_regex(r"(?<y>\d{4})_(?<m>\d{2})=>${m}/${y}")?,
+            ],
+            ..Default::default()
+        };
+        let mut headers = vec!["col_id".to_string(), "id".to_string(), "col_2024_05".to_string()];
+        rename_headers(&mut headers, &options)?;
+        assert_eq!(headers, vec!["id", "id", "05/2024"]);
+        assert_eq!(process_headers(headers), vec!["id", "id_1", "05/2024"]);
+        assert!(parse_rename_regex("no arrow").is_err());
+
+        let renamed = |strict: bool| {
+            let header_map = vec![("a".to_string(), "b".to_string()), ("b".to_string(), "a".to_string()), ("x".to_string(), "y".to_string())];
+            let options = FrameOptions { header_map, rename_strict: strict, ..Default::default() };
+            let mut headers = vec!["a".to_string(), "b".to_string()];
+            rename_headers(&mut headers, &options).map(|_| headers)
+        };
+        assert_eq!(renamed(false)?, vec!["b", "a"]);
+        assert!(renamed(true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_header_prefix_and_separator_keep_names_unique() -> Result<(), Box<dyn Error>> {
+        let text = |s: &str| Data::String(s.to_string());
+        let top = [text("Q1"), Data::Empty, Data::Empty, text("1")];
+        let sub = [text("Sales"), Data::Empty, Data::Empty, Data::Empty];
+        let options = FrameOptions {
+            header_separator: Some(String::new()),
+            unnamed_prefix: Some(String::new()),
+            ..Default::default()
+        };
+        let headers = collapse_multi_headers(&vec![&top[..], &sub[..]], &options, None)?;
+        assert_eq!(headers, vec!["Q1Sales", "1", "2", "1"]);
+        assert_eq!(process_headers(headers), vec!["Q1Sales", "1", "2", "1_1"]);
+        let headers = collapse_multi_headers(&vec![&sub[..]], &options, Some(2))?;
+        assert_eq!(headers, vec!["Sales", "D", "E", "F"]);
+        Ok(())
+    }
+
+    #[test]
+    fn fills_forward_only_the_top_header_row() -> Result<(), Box<dyn Error>> {
+        let text = |s: &str| Data::String(s.to_string());
+        let range = Range::from_sparse(vec![
+            calamine::Cell::new((0, 1), text("Sales")),
+            calamine::Cell::new((1, 0), text("Region")),
+            calamine::Cell::new((1, 1), text("Q1")),
+            calamine::Cell::new((1, 2), text("Q2")),
+            calamine::Cell::new((2, 0), text("North")),
+        ]);
+        let options = FrameOptions { header_fill_forward: true, header_separator: Some("/".to_string()), ..Default::default() };
+        let sheet = sheet_from_range(&range, Some(vec![0, 1]), &SheetPackage::default(), &options)?;
+        assert_eq!(sheet.headers, vec!["Region", "Sales/Q1", "Sales/Q2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn fills_forward_selected_header_rows() -> Result<(), Box<dyn Error>> {
+        let text = |s: &str| Data::String(s.to_string());
+        let range = Range::from_sparse(vec![
+            calamine::Cell::new((0, 0), text("Sales")),
+            calamine::Cell::new((1, 0), text("2023")),
+            calamine::Cell::new((1, 2), text("2024")),
+            calamine::Cell::new((2, 0), text("Q1")),
+            calamine::Cell::new((2, 1), text("Q2")),
+            calamine::Cell::new((2, 2), text("Q1")),
+            calamine::Cell::new((3, 0), Data::Float(1.0)),
+        ]);
+        let options = FrameOptions { ffill_header_rows: vec![0, 1], header_separator: Some("/".to_string()), ..Default::default() };
+        // Listed bottom-up, the indices still count from the top row
+        let sheet = sheet_from_range(&range, Some(vec![2, 1, 0]), &SheetPackage::default(), &options)?;
+        assert_eq!(sheet.headers, vec!["Q1/2023/Sales", "Q2/2023/Sales", "Q1/2024/Sales"]);
+        let options = FrameOptions { ffill_header_rows: vec![3], ..options };
+        assert!(sheet_from_range(&range, Some(vec![0, 1, 2]), &SheetPackage::default(), &options).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn finds_header_where_types_change() {
+        let text = |s: &str| Data::String(s.to_string());
+        let range = Range::from_sparse(vec![
+            calamine::Cell::new((0, 0), text("Sales report")),
+            calamine::Cell::new((1, 0), text("Year")),
+            calamine::Cell::new((1, 1), Data::Float(2024.0)),
+            calamine::Cell::new((3, 1), text("Q1")),
+            calamine::Cell::new((3, 2), text("Q2")),
+            calamine::Cell::new((4, 0), text("Region")),
+            calamine::Cell::new((4, 1), text("Units")),
+            calamine::Cell::new((4, 2), text("Units")),
+            calamine::Cell::new((5, 0), text("North")),
+            calamine::Cell::new((5, 1), Data::Float(1.0)),
+            calamine::Cell::new((5, 2), text("2")),
+            calamine::Cell::new((6, 0), text("South")),
+            calamine::Cell::new((6, 2), Data::Int(3)),
+        ]);
+        assert_eq!(type_change_header_row(&range), 4);
+        // Without typed data below, the first row stays the header
+        let words = Range::from_sparse(vec![calamine::Cell::new((0, 0), text("a")), calamine::Cell::new((1, 0), text("b"))]);
+        assert_eq!(type_change_header_row(&words), 0);
+    }
+
+    #[test]
+    fn appends_units_row_to_header_names() -> Result<(), Box<dyn Error>> {
+        let text = |s: &str| Data::String(s.to_string());
+        let range = Range::from_sparse(vec![
+            calamine::Cell::new((0, 0), text("Temperature")),
+            calamine::Cell::new((0, 1), text("Station")),
+            calamine::Cell::new((1, 0), text("°C")),
+            calamine::Cell::new((2, 0), Data::Float(21.5)),
+        ]);
+        let options = FrameOptions { units_row: Some(1), ..Default::default() };
+        let sheet = sheet_from_range(&range, None, &SheetPackage::default(), &options)?;
+        assert_eq!(sheet.headers, vec!["Temperature (°C)", "Station"]);
+        assert_eq!(sheet.data_rows().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn transliterates_headers_to_ascii() {
+        assert_eq!(transliterate("Время въезда"), "Vremya vezda");
+        assert_eq!(transliterate("ЩИТ Ёлка"), "ShchIT Elka");
+        assert_eq!(transliterate("Café Straße №"), "Cafe Strasse No");
+        assert_eq!(transliterate("Σ 日付"), "_ __");
+    }
+
+    #[test]
+    fn coalesces_columns_with_same_header() {
+        let text = |s: &str| Data::String(s.to_string());
+        let sheet = SheetData {
+            headers: vec!["id".into(), "name".into(), "name".into()],
+            rows: vec![
+                vec![text("id"), text("name"), text("name")],
+                vec![text("1"), text("a"), Data::Empty],
+                vec![text("2"), Data::Empty, text("b")],
+            ],
+            data_start: 1,
+        };
+        let sheet = coalesce_columns(sheet);
+        assert_eq!(sheet.headers, vec!["id", "name"]);
+        assert_eq!(sheet.data_rows(), &[vec![text("1"), text("a")], vec![text("2"), text("b")]]);
+    }
+
+    #[test]
+    fn limits_cell_length_on_character_boundaries() {
+        let options = FrameOptions { max_cell_length: Some(3), ..Default::default() };
+        let mut rows = vec![vec![Data::String("Дата".into()), Data::String("abc".into()), Data::Float(12345.5)]];
+        assert_eq!(limit_cell_length(&mut rows, &options), 1);
+        assert_eq!(rows[0], vec![Data::String("Да…".into()), Data::String("abc".into()), Data::Float(12345.5)]);
+    }
+
+    #[test]
+    fn strips_currency_symbols_and_thousands_separators() {
+        let symbols = ["$".to_string(), "€".to_string(), "₽".to_string()];
+        let strip = |text: &str| strip_currency(text, &symbols);
+        assert_eq!(strip("$1,234.56").as_deref(), Some("1234.56"));
+        assert_eq!(strip("€1.234,56").as_deref(), Some("1234.56"));
+        assert_eq!(strip("1 234 567 ₽").as_deref(), Some("1234567"));
+        assert_eq!(strip("€1,50").as_deref(), Some("1.50"));
+        assert_eq!(strip("$1,234").as_deref(), Some("1234"));
+        assert_eq!(strip("-$12").as_deref(), Some("-12"));
+        assert_eq!(strip("1,234.56"), None);
+        assert_eq!(strip("Price $"), None);
+    }
+
+    #[test]
+    fn strips_thousands_only_in_numeric_columns() {
+        let text = |s: &str| Data::String(s.to_string());
+        let mut rows = vec![
+            vec![text("1,234,567"), text("1,234"), text("Smith, John")],
+            vec![text("-12.5"), text("1,5"), text("7")],
+        ];
+        let options = FrameOptions { thousands_separator: Some(",".to_string()), ..Default::default() };
+        strip_thousands(&mut rows, &options);
+        assert_eq!(rows[0], vec![text("1234567"), text("1,234"), text("Smith, John")]);
+        assert_eq!(rows[1], vec![text("-12.5"), text("1,5"), text("7")]);
+        assert_eq!(without_thousands("1.234.567", "."), Some("1234567".to_string()));
+        assert_eq!(without_thousands("12,34", ","), None);
+    }
+
+    #[test]
+    fn header_widths_count_up_to_the_last_filled_cell() {
+        let text = |s: &str| Data::String(s.to_string());
+        let top = [text("Region"), text("Sales"), Data::Empty];
+        let sub = [text("North"), text("Q1"), text(" ")];
+        let short = [text("North"), Data::Empty, Data::Empty];
+        assert!(check_header_widths(&[0, 1], &[&top[..], &sub[..]], true).is_ok());
+        assert!(check_header_widths(&[0, 1], &[&top[..], &short[..]], false).is_ok());
+        let err = check_header_widths(&[0, 1], &[&top[..], &short[..]], true).unwrap_err();
+        assert_eq!(err.to_string(), "Header rows have different widths (row 0: 2 columns, row 1: 1 columns)");
+    }
+
+    #[test]
+    fn header_collapse_picks_the_parts_of_a_name() -> Result<(), Box<dyn Error>> {
+        let text = |s: &str| Data::String(s.to_string());
+        let top = [text("Sales"), Data::Empty];
+        let sub = [text("Units"), text("Price")];
+        let collapse = |header_collapse| {
+            let options = FrameOptions { header_collapse, ..Default::default() };
+            collapse_multi_headers(&vec![&top[..], &sub[..]], &options, None)
+        };
+        assert_eq!(collapse(HeaderCollapse::Join)?, vec!["Sales Units", "Price"]);
+        assert_eq!(collapse(HeaderCollapse::First)?, vec!["Sales", "Price"]);
+        assert_eq!(collapse(HeaderCollapse::Last)?, vec!["Units", "Price"]);
+        assert_eq!(collapse(HeaderCollapse::Nested)?, vec!["Sales::Units", "Price"]);
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes_only_headers_starting_with_a_digit() {
+        let mut headers = vec!["2024".to_string(), "Q1".to_string(), "3rd".to_string()];
+        prefix_numeric_headers(&mut headers, &FrameOptions::default());
+        assert_eq!(headers, ["2024", "Q1", "3rd"]);
+        let options = FrameOptions { numeric_header_prefix: Some("col_".to_string()), ..Default::default() };
+        prefix_numeric_headers(&mut headers, &options);
+        assert_eq!(headers, ["col_2024", "Q1", "col_3rd"]);
+    }
+
+    #[test]
+    fn last_row_counts_excel_rows_below_the_range_start() -> Result<(), Box<dyn Error>> {
+        // The used range starts at Excel row 3
+        let mut cells = vec![calamine::Cell::new((2, 0), Data::String("id".to_string()))];
+        cells.extend((3..8).map(|row| calamine::Cell::new((row, 0), Data::Float(row as f64))));
+        let range = Range::from_sparse(cells);
+        let options = FrameOptions { last_row: Some(5), ..Default::default() };
+        let sheet = sheet_from_range(&range, None, &SheetPackage::default(), &options)?;
+        assert_eq!(sheet.data_rows(), [vec![Data::Float(3.0)], vec![Data::Float(4.0)]]);
+        // A last row above the header leaves no rows to read it from
+        let options = FrameOptions { last_row: Some(2), ..Default::default() };
+        assert!(sheet_from_range(&range, None, &SheetPackage::default(), &options).is_err());
+        Ok(())
+    }
+}
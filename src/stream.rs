@@ -0,0 +1,247 @@
+use calamine::{Data, Xlsx, XlsxError};
+use polars::prelude::*;
+use std::io::{Read, Seek};
+
+use excel_reader::frame::cell_text;
+
+
+/// A cell's worksheet row, column and value.
+type StreamedCell = (u32, u32, Data);
+
+
+/// Worksheet rows read straight from the sheet XML, without loading the whole range.
+///
+/// Rows are numbered from the first row with a value, like calamine's
+/// ranges, and rows without values in between come back empty. Cells are
+/// placed by their worksheet column, starting from column A.
+pub struct RowStream<F> {
+    cells: F,
+    pending: Option<StreamedCell>,
+    // The reader fails when asked again after the end of the sheet
+    finished: bool,
+    first_row: Option<u32>,
+    row: Option<u32>,
+}
+
+/// Streams the rows of the worksheet called `sheet_name`.
+pub fn sheet_rows<'a, RS: Read + Seek>(
+    workbook: &'a mut Xlsx<RS>,
+    sheet_name: &str,
+) -> Result<RowStream<impl FnMut() -> Result<Option<StreamedCell>, XlsxError> + 'a>, XlsxError> {
+    let mut reader = workbook.worksheet_cells_reader(sheet_name)?;
+    let cells = move || {
+        let cell = reader.next_cell()?;
+        Ok(cell.map(|cell| (cell.get_position().0, cell.get_position().1, Data::from(cell.get_value().clone()))))
+    };
+    Ok(RowStream { cells, pending: None, finished: false, first_row: None, row: None })
+}
+
+impl<F: FnMut() -> Result<Option<StreamedCell>, XlsxError>> RowStream<F> {
+    /// The 0-based worksheet row of the first row with a value, once one was read.
+    pub fn first_row(&self) -> Option<u32> {
+        self.first_row
+    }
+
+    pub fn next_row(&mut self) -> Result<Option<Vec<Data>>, XlsxError> {
+        let Some(mut cell) = self.next_cell()? else { return Ok(None) };
+        self.first_row.get_or_insert(cell.0);
+        let row = *self.row.get_or_insert(cell.0);
+        self.row = Some(row + 1);
+        let mut values = Vec::new();
+        loop {
+            let (cell_row, col, value) = cell;
+            if cell_row > row {
+                self.pending = Some((cell_row, col, value));
+                break;
+            }
+            let col = col as usize;
+            if values.len() <= col {
+                values.resize(col + 1, Data::Empty);
+            }
+            values[col] = value;
+            match self.next_cell()? {
+                Some(next) => cell = next,
+                None => break,
+            }
+        }
+        Ok(Some(values))
+    }
+
+    /// The next cell with a value, skipping cells that only carry a style.
+    fn next_cell(&mut self) -> Result<Option<StreamedCell>, XlsxError> {
+        if let Some(cell) = self.pending.take() {
+            return Ok(Some(cell));
+        }
+        while !self.finished {
+            match (self.cells)()? {
+                Some(cell) if cell.2 != Data::Empty => return Ok(Some(cell)),
+                Some(_) => {}
+                None => self.finished = true,
+            }
+        }
+        Ok(None)
+    }
+}
+
+
+/// A column type settled from the sample rows of a streamed sheet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StreamType {
+    Boolean,
+    Int64,
+    Float64,
+    String,
+}
+
+impl StreamType {
+    /// The narrowest type that holds every non-empty cell, text when there are none.
+    pub fn infer<'c>(cells: impl IntoIterator<Item = &'c Data>) -> Self {
+        let mut inferred = None;
+        for cell in cells {
+            let kind = match cell {
+                Data::Empty => continue,
+                Data::Bool(_) => StreamType::Boolean,
+                Data::Int(_) => StreamType::Int64,
+                Data::Float(f) if as_integer(*f).is_some() => StreamType::Int64,
+                Data::Float(_) => StreamType::Float64,
+                _ => StreamType::String,
+            };
+            inferred = Some(match (inferred, kind) {
+                (None, kind) => kind,
+                (Some(seen), kind) if seen == kind => kind,
+                (Some(StreamType::Int64 | StreamType::Float64), StreamType::Int64 | StreamType::Float64) => StreamType::Float64,
+                _ => StreamType::String,
+            });
+        }
+        inferred.unwrap_or(StreamType::String)
+    }
+}
+
+/// `f` as an i64 when it is a whole number in range.
+fn as_integer(f: f64) -> Option<i64> {
+    (f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64).then_some(f as i64)
+}
+
+
+/// The values of one column for the batch being built.
+enum ColumnBuffer {
+    Boolean(Vec<Option<bool>>),
+    Int64(Vec<Option<i64>>),
+    Float64(Vec<Option<f64>>),
+    String(Vec<Option<String>>),
+}
+
+impl ColumnBuffer {
+    fn new(kind: StreamType) -> Self {
+        match kind {
+            StreamType::Boolean => ColumnBuffer::Boolean(Vec::new()),
+            StreamType::Int64 => ColumnBuffer::Int64(Vec::new()),
+            StreamType::Float64 => ColumnBuffer::Float64(Vec::new()),
+            StreamType::String => ColumnBuffer::String(Vec::new()),
+        }
+    }
+
+    /// Appends `cell`, as null when it doesn't fit the column type, and tells whether it fit.
+    fn push(&mut self, cell: Option<&Data>) -> bool {
+        let cell = cell.filter(|cell| **cell != Data::Empty);
+        match self {
+            ColumnBuffer::Boolean(values) => {
+                let value = cell.and_then(|cell| match cell {
+                    Data::Bool(b) => Some(*b),
+                    _ => None,
+                });
+                values.push(value);
+                cell.is_none() || value.is_some()
+            }
+            ColumnBuffer::Int64(values) => {
+                let value = cell.and_then(|cell| match cell {
+                    Data::Int(i) => Some(*i),
+                    Data::Float(f) => as_integer(*f),
+                    _ => None,
+                });
+                values.push(value);
+                cell.is_none() || value.is_some()
+            }
+            ColumnBuffer::Float64(values) => {
+                let value = cell.and_then(|cell| match cell {
+                    Data::Int(i) => Some(*i as f64),
+                    Data::Float(f) => Some(*f),
+                    _ => None,
+                });
+                values.push(value);
+                cell.is_none() || value.is_some()
+            }
+            ColumnBuffer::String(values) => {
+                let mut buffer = String::new();
+                values.push(cell.map(|cell| cell_text(Some(cell), &mut buffer).to_string()));
+                true
+            }
+        }
+    }
+
+    fn take(&mut self, name: &str) -> Column {
+        let name = PlSmallStr::from_str(name);
+        match self {
+            ColumnBuffer::Boolean(values) => Column::new(name, std::mem::take(values)),
+            ColumnBuffer::Int64(values) => Column::new(name, std::mem::take(values)),
+            ColumnBuffer::Float64(values) => Column::new(name, std::mem::take(values)),
+            ColumnBuffer::String(values) => Column::new(name, std::mem::take(values)),
+        }
+    }
+}
+
+
+/// Collects streamed rows into typed columns, one batch DataFrame at a time.
+///
+/// Cells that don't fit their column's sampled type become null, and cells
+/// outside the sampled columns are left out; both are counted.
+pub struct BatchBuilder {
+    names: Vec<String>,
+    first_col: usize,
+    columns: Vec<ColumnBuffer>,
+    rows: usize,
+    pub nulled: usize,
+    pub dropped: usize,
+}
+
+impl BatchBuilder {
+    /// Columns start at worksheet column `first_col` of the streamed rows.
+    pub fn new(names: Vec<String>, types: &[StreamType], first_col: usize) -> Self {
+        let columns = types.iter().map(|kind| ColumnBuffer::new(*kind)).collect();
+        BatchBuilder { names, first_col, columns, rows: 0, nulled: 0, dropped: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn push_row(&mut self, row: &[Data]) {
+        for (col_idx, column) in self.columns.iter_mut().enumerate() {
+            if !column.push(row.get(self.first_col + col_idx)) {
+                self.nulled += 1;
+            }
+        }
+        let columns = self.first_col..self.first_col + self.columns.len();
+        self.dropped += row
+            .iter()
+            .enumerate()
+            .filter(|(col, cell)| !columns.contains(col) && **cell != Data::Empty)
+            .count();
+        self.rows += 1;
+    }
+
+    /// The rows pushed since the last batch.
+    pub fn take(&mut self) -> PolarsResult<DataFrame> {
+        self.rows = 0;
+        let columns = self.names.iter().zip(&mut self.columns).map(|(name, column)| column.take(name)).collect();
+        DataFrame::new(columns)
+    }
+}
+
+
+/// How `--stream` batches and types the rows.
+#[derive(Clone, Copy, Debug)]
+pub struct StreamSettings {
+    pub batch_rows: usize,
+    pub sample_rows: usize,
+}
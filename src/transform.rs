@@ -0,0 +1,577 @@
+use polars::prelude::*;
+use polars_ops::pivot::{PivotAgg, UnpivotDF, pivot_stable};
+use polars_ops::series::{RoundMode, RoundSeries};
+use std::error::Error;
+
+
+/// The `--columns` to keep, in the listed order or, with `sheet_order`, in the order they have in `df`.
+///
+/// An entry may list fallbacks, e.g. "Sales|Revenue|@4", tried left to right,
+/// where `@N` is the column at 0-based position N. Taking a fallback prints a
+/// warning, so header drift doesn't go unnoticed.
+pub fn selected_columns(df: &DataFrame, columns: &[String], sheet_order: bool) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut selected = Vec::with_capacity(columns.len());
+    let mut missing = Vec::new();
+    for spec in columns {
+        match resolve_column(df, spec) {
+            Some(name) => {
+                if spec.contains('|') && spec.split('|').next() != Some(name.as_str()) {
+                    eprintln!("Warning: --columns entry '{}' took the fallback column '{}'", spec, name);
+                }
+                selected.push(name);
+            }
+            None => missing.push(spec.as_str()),
+        }
+    }
+    if !missing.is_empty() {
+        let available: Vec<&str> = df.get_column_names_str();
+        return Err(format!("--columns names missing columns [{}], available: [{}]", missing.join(", "), available.join(", ")).into());
+    }
+    if sheet_order {
+        let kept = df.get_column_names_str().into_iter().filter(|name| selected.iter().any(|c| c == name));
+        return Ok(kept.map(|name| name.to_string()).collect());
+    }
+    Ok(selected)
+}
+
+/// The column of `df` a `--columns` entry names, trying the whole entry as a name first.
+fn resolve_column(df: &DataFrame, spec: &str) -> Option<String> {
+    if df.get_column_index(spec).is_some() {
+        return Some(spec.to_string());
+    }
+    spec.split('|').find_map(|alternative| match alternative.strip_prefix('@').and_then(|n| n.parse::<usize>().ok()) {
+        Some(index) => df.get_column_names_str().get(index).map(|name| name.to_string()),
+        None => df.get_column_index(alternative).map(|_| alternative.to_string()),
+    })
+}
+
+
+/// Counts rows per distinct combination of `columns`, most frequent first.
+///
+/// Ties are ordered by the key values so the output is stable between runs.
+/// The counts are the last column, "count", or "count_1" and so on when a
+/// grouping column already has that name.
+pub fn count_by(df: &DataFrame, columns: &[String]) -> PolarsResult<DataFrame> {
+    let groups = df.group_by(columns)?;
+    let mut counted = groups.keys();
+    let name = std::iter::once("count".to_string())
+        .chain((1..).map(|n| format!("count_{}", n)))
+        .find(|name| !columns.contains(name))
+        .unwrap_or_default();
+    let mut counts = groups.get_groups().group_count();
+    counts.rename(name.as_str().into());
+    counted.push(counts.into_column());
+
+    let mut order = vec![name];
+    order.extend(columns.iter().cloned());
+    let mut descending = vec![false; order.len()];
+    descending[0] = true;
+    DataFrame::new(counted)?.sort(
+        order,
+        SortMultipleOptions::default()
+            .with_order_descending_multi(descending)
+            .with_nulls_last(true),
+    )
+}
+
+
+/// Drops rows that repeat an earlier one in every column, or in `subset` when given.
+///
+/// The kept rows stay in their original order, `keep_last` keeps the last of
+/// each set of duplicates instead of the first.
+pub fn drop_duplicate_rows(df: &DataFrame, subset: Option<&[String]>, keep_last: bool) -> PolarsResult<DataFrame> {
+    let keep = if keep_last { UniqueKeepStrategy::Last } else { UniqueKeepStrategy::First };
+    df.unique_stable(subset, keep, None)
+}
+
+
+/// Row counts behind a `--count-distinct` check of one or more key columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DistinctCount {
+    pub rows: usize,
+    /// Rows where none of the key columns is null
+    pub non_null: usize,
+    /// Distinct combinations among the non-null rows
+    pub distinct: usize,
+}
+
+impl DistinctCount {
+    /// Whether every row has its own non-null key.
+    pub fn is_unique(&self) -> bool {
+        self.distinct == self.rows
+    }
+}
+
+
+/// Counts the distinct non-null combinations of `columns`, for key validation.
+pub fn count_distinct(df: &DataFrame, columns: &[String]) -> PolarsResult<DistinctCount> {
+    let keys = df.select(columns)?.drop_nulls::<String>(None)?;
+    let distinct = match keys.height() {
+        0 => 0,
+        _ => keys.group_by(columns)?.get_groups().len(),
+    };
+    Ok(DistinctCount { rows: df.height(), non_null: keys.height(), distinct })
+}
+
+
+/// The non-null combinations of `columns` found in more than one row, with their `count`.
+pub fn duplicate_keys(df: &DataFrame, columns: &[String]) -> PolarsResult<DataFrame> {
+    let keys = df.select(columns)?.drop_nulls::<String>(None)?;
+    if keys.height() == 0 {
+        return Ok(keys);
+    }
+    let counted = count_by(&keys, columns)?;
+    let repeated = counted.get_columns()[columns.len()].as_materialized_series().gt(1)?;
+    counted.filter(&repeated)
+}
+
+
+/// A `--pivot` specification, e.g. "index=Region;columns=Month;values=Sales;agg=sum".
+#[derive(Clone, Debug, PartialEq)]
+pub struct PivotSpec {
+    pub index: Vec<String>,
+    pub columns: Vec<String>,
+    pub values: Vec<String>,
+    pub agg: Option<String>,
+}
+
+const PIVOT_AGGS: &[&str] = &["first", "last", "sum", "min", "max", "mean", "median", "count"];
+
+/// Parses `;`-separated `key=value` pairs, list values are comma-separated.
+///
+/// `columns` is required. Without `index` or `values` Polars uses the
+/// remaining columns, without `agg` duplicate index/column pairs are an error.
+pub fn parse_pivot_spec(spec: &str) -> Result<PivotSpec, Box<dyn Error>> {
+    let mut pivot = PivotSpec { index: Vec::new(), columns: Vec::new(), values: Vec::new(), agg: None };
+    for part in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid pivot setting '{}', expected key=value", part))?;
+        let list = || value.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+        match key.trim() {
+            "index" => pivot.index = list(),
+            "columns" => pivot.columns = list(),
+            "values" => pivot.values = list(),
+            "agg" => {
+                let agg = value.trim().to_ascii_lowercase();
+                if !PIVOT_AGGS.contains(&agg.as_str()) {
+                    return Err(format!("Unknown pivot agg '{}', expected one of {}", value.trim(), PIVOT_AGGS.join(", ")).into());
+                }
+                pivot.agg = Some(agg);
+            }
+            other => return Err(format!("Unknown pivot setting '{}', expected index, columns, values or agg", other).into()),
+        }
+    }
+    if pivot.columns.is_empty() {
+        return Err(format!("Pivot spec '{}' needs columns=...", spec).into());
+    }
+    Ok(pivot)
+}
+
+
+/// Turns the distinct values of the `columns` columns into new columns.
+///
+/// Rows keep the order in which each index value first appears, the new
+/// columns are sorted by name so repeated runs give the same layout.
+pub fn pivot(df: &DataFrame, spec: &PivotSpec) -> PolarsResult<DataFrame> {
+    let agg = spec.agg.as_deref().map(|agg| match agg {
+        "last" => PivotAgg::Last,
+        "sum" => PivotAgg::Sum,
+        "min" => PivotAgg::Min,
+        "max" => PivotAgg::Max,
+        "mean" => PivotAgg::Mean,
+        "median" => PivotAgg::Median,
+        "count" => PivotAgg::Count,
+        _ => PivotAgg::First,
+    });
+    let index = (!spec.index.is_empty()).then_some(&spec.index);
+    let values = (!spec.values.is_empty()).then_some(&spec.values);
+    pivot_stable(df, &spec.columns, index, values, true, agg, None)
+}
+
+
+/// A `--group-agg` specification, e.g. "by=Region;sum=Sales,Units;mean=Margin".
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupAggSpec {
+    pub by: Vec<String>,
+    /// `(function, column)` pairs in the order given
+    pub aggs: Vec<(String, String)>,
+}
+
+const GROUP_AGGS: &[&str] = &["sum", "mean", "median", "min", "max", "first", "last", "count", "n_unique"];
+
+/// Parses `;`-separated `key=value` pairs like `parse_pivot_spec`.
+///
+/// `by` lists the grouping columns, every other key is an aggregation
+/// function applied to its comma-separated columns.
+pub fn parse_group_agg_spec(spec: &str) -> Result<GroupAggSpec, Box<dyn Error>> {
+    let mut group = GroupAggSpec { by: Vec::new(), aggs: Vec::new() };
+    for part in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid group-agg setting '{}', expected key=value", part))?;
+        let list = value.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        match key.trim().to_ascii_lowercase().as_str() {
+            "by" => group.by = list.collect(),
+            agg if GROUP_AGGS.contains(&agg) => group.aggs.extend(list.map(|column| (agg.to_string(), column))),
+            other => {
+                return Err(format!("Unknown group-agg setting '{}', expected by or one of {}", other, GROUP_AGGS.join(", ")).into());
+            }
+        }
+    }
+    if group.by.is_empty() || group.aggs.is_empty() {
+        return Err(format!("Group-agg spec '{}' needs by=... and at least one aggregation", spec).into());
+    }
+    Ok(group)
+}
+
+
+/// One row per distinct `by` combination with a "{column}_{function}" column per aggregation.
+///
+/// Groups keep the order in which they first appear.
+pub fn group_agg(df: &DataFrame, spec: &GroupAggSpec) -> PolarsResult<DataFrame> {
+    group_agg_lazy(df.clone().lazy(), spec).collect()
+}
+
+/// The `--group-agg` step as a lazy query, for `group_agg` and `--explain-lazy`.
+pub fn group_agg_lazy(lf: LazyFrame, spec: &GroupAggSpec) -> LazyFrame {
+    let aggs: Vec<Expr> = spec
+        .aggs
+        .iter()
+        .map(|(agg, column)| {
+            let expr = col(column.as_str());
+            let expr = match agg.as_str() {
+                "sum" => expr.sum(),
+                "mean" => expr.mean(),
+                "median" => expr.median(),
+                "min" => expr.min(),
+                "max" => expr.max(),
+                "first" => expr.first(),
+                "last" => expr.last(),
+                "count" => expr.count(),
+                _ => expr.n_unique(),
+            };
+            expr.alias(format!("{}_{}", column, agg))
+        })
+        .collect();
+    let by: Vec<Expr> = spec.by.iter().map(|c| col(c.as_str())).collect();
+    lf.group_by_stable(by).agg(aggs)
+}
+
+
+/// A `--fill-na` setting: one value for every column, or values per column.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FillNa {
+    All(String),
+    Columns(Vec<(String, String)>),
+}
+
+/// Parses "value" or "column=value,column=value".
+pub fn parse_fill_na(spec: &str) -> Result<FillNa, Box<dyn Error>> {
+    if !spec.contains('=') {
+        return Ok(FillNa::All(spec.to_string()));
+    }
+    spec.split(',')
+        .map(|pair| match pair.split_once('=') {
+            Some((column, value)) if !column.trim().is_empty() => Ok((column.trim().to_string(), value.to_string())),
+            _ => Err(format!("Invalid fill-na entry '{}', expected column=value", pair).into()),
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()
+        .map(FillNa::Columns)
+}
+
+
+/// Replaces nulls, and empty strings in text columns, with a constant.
+///
+/// The value is cast to each column's type, so "0" fills a numeric column
+/// with the number 0. A value given for a specific column must fit its type,
+/// the catch-all form leaves columns it doesn't fit unchanged with a warning.
+pub fn fill_na(df: &DataFrame, fill: &FillNa) -> Result<DataFrame, Box<dyn Error>> {
+    let targets: Vec<(String, &str, bool)> = match fill {
+        FillNa::All(value) => df
+            .get_column_names()
+            .into_iter()
+            .map(|name| (name.to_string(), value.as_str(), false))
+            .collect(),
+        FillNa::Columns(pairs) => pairs.iter().map(|(name, value)| (name.clone(), value.as_str(), true)).collect(),
+    };
+
+    let mut df = df.clone();
+    for (name, value, strict) in targets {
+        let series = df.column(&name)?.as_materialized_series().clone();
+        let fill = match Series::new(series.name().clone(), [value]).strict_cast(series.dtype()) {
+            Ok(fill) => fill,
+            Err(_) if strict => {
+                return Err(format!("--fill-na value '{}' does not fit column '{}' of type {}", value, name, series.dtype()).into());
+            }
+            Err(_) => {
+                eprintln!("Warning: --fill-na value '{}' left column '{}' of type {} unchanged", value, name, series.dtype());
+                continue;
+            }
+        };
+        let mut missing = series.is_null();
+        if let Ok(text) = series.str() {
+            missing = missing | text.equal("");
+        }
+        let filled = fill.new_from_index(0, series.len()).zip_with(&missing, &series)?;
+        df.replace(&name, filled)?;
+    }
+    Ok(df)
+}
+
+
+/// A `--melt` specification, e.g. "id=Region,Product;value-name=sales;var-name=year".
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeltSpec {
+    pub id: Vec<String>,
+    pub columns: Vec<String>,
+    pub var_name: Option<String>,
+    pub value_name: Option<String>,
+}
+
+/// Parses `;`-separated `key=value` pairs like `parse_pivot_spec`.
+///
+/// `columns` lists the columns to stack, without it every non-id column is
+/// stacked. `var-name` and `value-name` default to Polars' "variable" and
+/// "value".
+pub fn parse_melt_spec(spec: &str) -> Result<MeltSpec, Box<dyn Error>> {
+    let mut melt = MeltSpec { id: Vec::new(), columns: Vec::new(), var_name: None, value_name: None };
+    for part in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid melt setting '{}', expected key=value", part))?;
+        let list = || value.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+        match key.trim() {
+            "id" => melt.id = list(),
+            "columns" => melt.columns = list(),
+            "var-name" => melt.var_name = Some(value.trim().to_string()),
+            "value-name" => melt.value_name = Some(value.trim().to_string()),
+            other => {
+                return Err(format!("Unknown melt setting '{}', expected id, columns, var-name or value-name", other).into());
+            }
+        }
+    }
+    if melt.id.is_empty() && melt.columns.is_empty() {
+        return Err(format!("Melt spec '{}' needs id=... or columns=...", spec).into());
+    }
+    Ok(melt)
+}
+
+
+/// Stacks the non-id columns into variable/value rows, the inverse of `pivot`.
+///
+/// Values of different types are stacked in their common supertype, e.g.
+/// text when some year columns hold numbers and others text.
+pub fn melt(df: &DataFrame, spec: &MeltSpec) -> PolarsResult<DataFrame> {
+    df.unpivot2(UnpivotArgsIR {
+        on: spec.columns.iter().map(|c| c.as_str().into()).collect(),
+        index: spec.id.iter().map(|c| c.as_str().into()).collect(),
+        variable_name: spec.var_name.as_deref().map(Into::into),
+        value_name: spec.value_name.as_deref().map(Into::into),
+    })
+}
+
+
+/// Parses a `--round` "column:decimals,column:decimals" list.
+pub fn parse_round_spec(spec: &str) -> Result<Vec<(String, u32)>, Box<dyn Error>> {
+    spec.split(',')
+        .map(|pair| match pair.rsplit_once(':') {
+            Some((column, decimals)) if !column.trim().is_empty() => match decimals.trim().parse() {
+                Ok(decimals) => Ok((column.trim().to_string(), decimals)),
+                Err(_) => Err(format!("Invalid --round decimals '{}' for column '{}'", decimals, column.trim()).into()),
+            },
+            _ => Err(format!("Invalid --round entry '{}', expected column:decimals", pair).into()),
+        })
+        .collect()
+}
+
+
+/// Rounds float and decimal columns to the given number of decimals, halves away from zero as Excel does.
+///
+/// Integer columns are already whole and stay as they are, other types are an error.
+pub fn round_columns(df: &DataFrame, columns: &[(String, u32)]) -> Result<DataFrame, Box<dyn Error>> {
+    let mut df = df.clone();
+    for (name, decimals) in columns {
+        let series = df.column(name)?.as_materialized_series();
+        let rounded = match series.dtype() {
+            dtype if dtype.is_float() || dtype.is_decimal() => series.round(*decimals, RoundMode::HalfAwayFromZero)?,
+            dtype if dtype.is_integer() => continue,
+            dtype => return Err(format!("--round column '{}' is of type {}, not a number", name, dtype).into()),
+        };
+        df.replace(name, rounded)?;
+    }
+    Ok(df)
+}
+
+
+/// Renders Date and Datetime columns as text with a strftime pattern, e.g. "%d.%m.%Y".
+///
+/// Other columns are left alone, so this is meant to run just before writing.
+pub fn format_dates(df: &DataFrame, format: &str) -> Result<DataFrame, Box<dyn Error>> {
+    if chrono::format::StrftimeItems::new(format).any(|item| matches!(item, chrono::format::Item::Error)) {
+        return Err(format!("Invalid --date-format '{}'", format).into());
+    }
+    let mut df = df.clone();
+    let temporal: Vec<String> = df
+        .get_columns()
+        .iter()
+        .filter(|c| matches!(c.dtype(), DataType::Date | DataType::Datetime(_, _)))
+        .map(|c| c.name().to_string())
+        .collect();
+    for name in temporal {
+        let series = df.column(&name)?.as_materialized_series();
+        let text = match series.dtype() {
+            DataType::Date => series.date()?.to_string(format)?,
+            _ => series.datetime()?.to_string(format)?,
+        };
+        df.replace(&name, text.into_series())?;
+    }
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_repeated_composite_keys() -> Result<(), Box<dyn Error>> {
+        let df = df!(
+            "Id" => [Some(1), Some(1), Some(2), Some(2), None, None],
+            "Year" => [2023, 2023, 2023, 2024, 2023, 2023],
+        )?;
+        let duplicates = duplicate_keys(&df, &["Id".to_string()])?;
+        assert_eq!(duplicates.column("Id")?.i32()?.to_vec(), [Some(1), Some(2)]);
+        assert_eq!(duplicates.column("count")?.u32()?.to_vec(), [Some(2), Some(2)]);
+        let key = ["Id".to_string(), "Year".to_string()];
+        assert_eq!(duplicate_keys(&df, &key)?.height(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn columns_fall_back_to_other_names_and_positions() -> Result<(), Box<dyn Error>> {
+        let df = df!("Region" => ["North"], "Revenue" => [1.0], "Month" => ["Jan"])?;
+        let columns = ["Sales|Revenue", "Month", "Total|@0"].map(String::from);
+        assert_eq!(selected_columns(&df, &columns, false)?, ["Revenue", "Month", "Region"]);
+        assert_eq!(selected_columns(&df, &columns, true)?, ["Region", "Revenue", "Month"]);
+        assert!(selected_columns(&df, &["Sales|@3".to_string()], false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rounds_float_columns_half_away_from_zero() -> Result<(), Box<dyn Error>> {
+        let df = df!("margin" => [0.125, -2.5], "count" => [1i64, 2], "name" => ["a", "b"])?;
+        let df = round_columns(&df, &parse_round_spec("margin:2,count:0")?)?;
+        assert_eq!(df.column("margin")?.f64()?.into_no_null_iter().collect::<Vec<_>>(), [0.13, -2.5]);
+        assert_eq!(df.column("count")?.dtype(), &DataType::Int64);
+        assert!(round_columns(&df, &[("name".to_string(), 1)]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn count_by_keeps_a_count_column_apart() -> Result<(), Box<dyn Error>> {
+        let df = df!("count" => [3, 3, 5], "count_1" => ["a", "a", "b"])?;
+        let key = ["count".to_string(), "count_1".to_string()];
+        let counted = count_by(&df, &key)?;
+        let names: Vec<&str> = counted.get_column_names().into_iter().map(|name| name.as_str()).collect();
+        assert_eq!(names, ["count", "count_1", "count_2"]);
+        assert_eq!(counted.column("count_2")?.u32()?.to_vec(), [Some(2), Some(1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn pivots_values_into_sorted_columns() -> Result<(), Box<dyn Error>> {
+        let df = df!(
+            "Region" => ["South", "North", "North", "South"],
+            "Month" => ["Jan", "Feb", "Jan", "Jan"],
+            "Sales" => [3.0, 1.0, 2.0, 4.0],
+        )?;
+        let spec = parse_pivot_spec("index=Region; columns=Month; values=Sales")?;
+        // South has two January rows, which need an agg
+        assert!(pivot(&df, &spec).is_err());
+        let spec = parse_pivot_spec("index=Region; columns=Month; values=Sales; agg=sum")?;
+        let pivoted = pivot(&df, &spec)?;
+        let names: Vec<&str> = pivoted.get_column_names().into_iter().map(|name| name.as_str()).collect();
+        assert_eq!(names, ["Region", "Feb", "Jan"]);
+        let regions: Vec<&str> = pivoted.column("Region")?.str()?.into_no_null_iter().collect();
+        assert_eq!(regions, ["South", "North"]);
+        let feb: Vec<Option<f64>> = pivoted.column("Feb")?.f64()?.into_iter().collect();
+        assert_eq!(feb, [None, Some(1.0)]);
+        let jan: Vec<Option<f64>> = pivoted.column("Jan")?.f64()?.into_iter().collect();
+        assert_eq!(jan, [Some(7.0), Some(2.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn drops_duplicates_on_a_subset_keeping_the_last() -> Result<(), Box<dyn Error>> {
+        let df = df!("id" => [1, 2, 1, 3, 2, 1], "seen" => ["a", "b", "c", "d", "e", "f"])?;
+        let subset = ["id".to_string()];
+        let seen = |df: DataFrame| -> PolarsResult<Vec<String>> {
+            Ok(df.column("seen")?.str()?.into_no_null_iter().map(String::from).collect())
+        };
+        assert_eq!(seen(drop_duplicate_rows(&df, Some(&subset), false)?)?, ["a", "b", "d"]);
+        // The last of each id is kept, in the order the kept rows had
+        assert_eq!(seen(drop_duplicate_rows(&df, Some(&subset), true)?)?, ["d", "e", "f"]);
+        assert_eq!(drop_duplicate_rows(&df, None, true)?.height(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn melt_stacks_every_non_id_column() -> Result<(), Box<dyn Error>> {
+        assert!(parse_melt_spec("var-name=year").is_err());
+        assert!(parse_melt_spec("id=Region; rows=2023").is_err());
+
+        let df = df!("Region" => ["North", "South"], "2023" => [1i64, 2], "2024" => ["3", "n/a"])?;
+        let spec = parse_melt_spec("id=Region; var-name=year; value-name=sales")?;
+        assert!(spec.columns.is_empty());
+        let melted = melt(&df, &spec)?;
+        assert_eq!(melted.get_column_names_str(), ["Region", "year", "sales"]);
+        let years: Vec<&str> = melted.column("year")?.str()?.into_no_null_iter().collect();
+        assert_eq!(years, ["2023", "2023", "2024", "2024"]);
+        // An integer and a text column stack as text
+        let sales: Vec<&str> = melted.column("sales")?.str()?.into_no_null_iter().collect();
+        assert_eq!(sales, ["1", "2", "3", "n/a"]);
+
+        let melted = melt(&df, &parse_melt_spec("id=Region; columns=2023")?)?;
+        assert_eq!(melted.get_column_names_str(), ["Region", "variable", "value"]);
+        assert_eq!(melted.column("value")?.dtype(), &DataType::Int64);
+        Ok(())
+    }
+
+    #[test]
+    fn group_agg_names_columns_after_the_column_and_function() -> Result<(), Box<dyn Error>> {
+        assert!(parse_group_agg_spec("by=Region; total=Sales").is_err());
+        assert!(parse_group_agg_spec("sum=Sales").is_err());
+        assert!(parse_group_agg_spec("by=Region").is_err());
+
+        let df = df!(
+            "Region" => ["South", "North", "South", "North", "East"],
+            "Sales" => [3.0, 1.0, 4.0, 2.0, 5.0],
+        )?;
+        let spec = parse_group_agg_spec("by=Region; sum=Sales; MAX=Sales; count=Sales")?;
+        let grouped = group_agg(&df, &spec)?;
+        assert_eq!(grouped.get_column_names_str(), ["Region", "Sales_sum", "Sales_max", "Sales_count"]);
+        // Groups come out in the order they first appear
+        let regions: Vec<&str> = grouped.column("Region")?.str()?.into_no_null_iter().collect();
+        assert_eq!(regions, ["South", "North", "East"]);
+        let sums: Vec<f64> = grouped.column("Sales_sum")?.f64()?.into_no_null_iter().collect();
+        assert_eq!(sums, [7.0, 3.0, 5.0]);
+        assert_eq!(grouped.column("Sales_count")?.u32()?.to_vec(), [Some(2), Some(2), Some(1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn fill_na_casts_the_value_to_each_column() -> Result<(), Box<dyn Error>> {
+        let df = df!("amount" => [Some(1.5), None], "note" => [Some(""), None])?;
+        let filled = fill_na(&df, &parse_fill_na("0")?)?;
+        let amounts: Vec<Option<f64>> = filled.column("amount")?.f64()?.into_iter().collect();
+        assert_eq!(amounts, [Some(1.5), Some(0.0)]);
+        let notes: Vec<Option<&str>> = filled.column("note")?.str()?.into_iter().collect();
+        assert_eq!(notes, [Some("0"), Some("0")]);
+
+        // The catch-all skips columns the value doesn't fit, a per-column value must fit
+        let filled = fill_na(&df, &parse_fill_na("n/a")?)?;
+        assert_eq!(filled.column("amount")?.null_count(), 1);
+        assert!(fill_na(&df, &parse_fill_na("amount=n/a")?).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+use calamine::{Data, Range, Reader, Xlsx, XlsxError, open_workbook};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::ExcelReaderError;
+
+
+/// Reads the raw cells of a worksheet, without headers, typing or conversion.
+///
+/// `sheet` is matched like `--worksheet`, ignoring stray whitespace, and
+/// `None` picks the first sheet. Formula cells hold their cached values.
+/// This is the entry point for callers who want to build their own pipeline
+/// on calamine's `Data` instead of a DataFrame; use `Range::rows` to walk the
+/// cells row by row.
+///
+/// ```no_run
+/// let range = excel_reader::read_raw("report.xlsx", Some("Sheet1"))?;
+/// for row in range.rows() {
+///     println!("{:?}", row);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn read_raw(path: &str, sheet: Option<&str>) -> Result<Range<Data>, Box<dyn Error>> {
+    let mut workbook = open_excel(path)?;
+    worksheet_range(&mut workbook, sheet, SheetMatch::default(), FormulaMode::Value)
+}
+
+
+/// The names of all worksheets, in workbook order.
+pub fn sheet_names(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(open_excel(path)?.sheet_names())
+}
+
+
+/// Whether the workbook has a sheet called exactly `name`.
+///
+/// Unlike `read_raw` this doesn't fail for a missing sheet, so callers can
+/// probe for it and fall back. A missing or unreadable file is still an
+/// error. Use `find_sheet_name` on `sheet_names` to match leniently.
+pub fn has_sheet(path: &str, name: &str) -> Result<bool, Box<dyn Error>> {
+    Ok(sheet_names(path)?.iter().any(|sheet| sheet == name))
+}
+
+
+/// Opens an .xlsx workbook, reporting a missing file as `ExcelReaderError::FileNotFound`.
+pub fn open_excel(path: &str) -> Result<Xlsx<BufReader<File>>, Box<dyn Error>> {
+    match open_workbook(path) {
+        Ok(workbook) => Ok(workbook),
+        Err(XlsxError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(ExcelReaderError::FileNotFound(path.to_string()).into())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+
+/// A Unicode normalization form, for text that arrives composed in some
+/// places and decomposed in others.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnicodeForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl UnicodeForm {
+    pub fn normalize(self, text: &str) -> String {
+        match self {
+            UnicodeForm::Nfc => text.nfc().collect(),
+            UnicodeForm::Nfd => text.nfd().collect(),
+            UnicodeForm::Nfkc => text.nfkc().collect(),
+            UnicodeForm::Nfkd => text.nfkd().collect(),
+        }
+    }
+}
+
+
+/// How a requested worksheet name is compared with the workbook's sheet names.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SheetMatch {
+    /// Don't ignore whitespace differences
+    pub exact: bool,
+    /// Compare both names in this normalization form
+    pub unicode: Option<UnicodeForm>,
+}
+
+
+/// Finds the sheet called `requested`.
+///
+/// An exact match always wins. Unless `matching.exact` is set, names are then
+/// compared with surrounding whitespace trimmed and inner runs of whitespace
+/// collapsed to one space, so "МАЙ 2024 " finds the sheet "МАЙ  2024". With
+/// `matching.unicode` both names are also normalized before comparing.
+pub fn find_sheet_name(sheet_names: &[String], requested: &str, matching: SheetMatch) -> Result<String, Box<dyn Error>> {
+    if let Some(name) = sheet_names.iter().find(|name| *name == requested) {
+        return Ok(name.clone());
+    }
+    if !matching.exact || matching.unicode.is_some() {
+        let normalize = |name: &str| {
+            let name = matching.unicode.map_or_else(|| name.to_string(), |form| form.normalize(name));
+            match matching.exact {
+                true => name,
+                false => name.split_whitespace().collect::<Vec<_>>().join(" "),
+            }
+        };
+        let wanted = normalize(requested);
+        let matches: Vec<&String> = sheet_names.iter().filter(|name| normalize(name) == wanted).collect();
+        match matches.as_slice() {
+            [name] => return Ok((*name).clone()),
+            [] => {}
+            _ => {
+                return Err(format!(
+                    "Worksheet name '{}' is ambiguous, it matches {:?}; use --exact-sheet",
+                    requested, matches
+                )
+                .into());
+            }
+        }
+    }
+    Err(ExcelReaderError::SheetNotFound(requested.to_string()).into())
+}
+
+
+/// Reads the cells of a worksheet, or of the first one when no name is given.
+///
+/// With `FormulaMode::Text` cells holding a formula show "=FORMULA" instead of
+/// the cached result.
+pub fn worksheet_range<RS: Read + Seek>(
+    workbook: &mut Xlsx<RS>,
+    worksheet_name: Option<&str>,
+    matching: SheetMatch,
+    formulas: FormulaMode,
+) -> Result<Range<Data>, Box<dyn Error>> {
+    let (name, mut range) = match worksheet_name {
+        Some(requested) => {
+            let name = find_sheet_name(&workbook.sheet_names(), requested, matching)?;
+            let range = workbook.worksheet_range(&name)?;
+            (name, range)
+        }
+        None => {
+            // The first sheet by name, so only that one is read
+            let name = workbook
+                .sheet_names()
+                .into_iter()
+                .next()
+                .ok_or("No worksheets found in the workbook")?;
+            let range = workbook.worksheet_range(&name)?;
+            (name, range)
+        }
+    };
+
+    if formulas == FormulaMode::Text {
+        let formula_range = workbook.worksheet_formula(&name)?;
+        overlay_formulas(&mut range, &formula_range);
+    }
+    Ok(range)
+}
+
+
+/// Whether formula cells yield their cached result or the formula itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FormulaMode {
+    #[default]
+    Value,
+    Text,
+}
+
+
+/// Replaces cached values with "=FORMULA" strings for cells that have a formula.
+fn overlay_formulas(range: &mut Range<Data>, formulas: &Range<String>) {
+    let Some((start_row, start_col)) = formulas.start() else {
+        return;
+    };
+    for (row, col, formula) in formulas.used_cells() {
+        if formula.is_empty() {
+            continue;
+        }
+        let position = (start_row + row as u32, start_col + col as u32);
+        // Formulas outside the value range have no cell to show them in
+        let in_range = range
+            .start()
+            .is_some_and(|(r, c)| r <= position.0 && c <= position.1);
+        if in_range {
+            let text = if formula.starts_with('=') { formula.clone() } else { format!("={}", formula) };
+            range.set_value(position, Data::String(text));
+        }
+    }
+}
+
+
+/// A rectangle of cells on one sheet, with 0-based inclusive `(row, column)` corners.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellArea {
+    pub sheet: String,
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+}
+
+
+/// Looks up a workbook-level defined name that points at a block of cells.
+///
+/// Names that refer to formulas, constants or several areas are rejected.
+pub fn named_range(workbook: &Xlsx<BufReader<File>>, name: &str) -> Result<CellArea, Box<dyn Error>> {
+    let (_, formula) = workbook
+        .defined_names()
+        .iter()
+        .find(|(defined, _)| defined == name)
+        .ok_or_else(|| {
+            let names = workbook
+                .defined_names()
+                .iter()
+                .map(|(defined, _)| defined.as_str())
+                .filter(|defined| !defined.starts_with("_xlnm."))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("Named range '{}' not found, available names: [{}]", name, names)
+        })?;
+    parse_area_ref(formula)
+        .ok_or_else(|| format!("Named range '{}' refers to '{}', which is not a single cell range", name, formula).into())
+}
+
+
+/// The last 0-based row of a worksheet, whole-column references like "A:C" end here.
+pub const LAST_ROW: u32 = 1_048_575;
+
+/// The last 0-based column of a worksheet, "XFD".
+pub const LAST_COL: u32 = 16_383;
+
+
+/// Parses "Sheet!$A$1:$D$100", "'My sheet'!B2" or "Sheet!A:C" into a `CellArea`.
+pub fn parse_area_ref(reference: &str) -> Option<CellArea> {
+    let (sheet, cells) = reference.trim().trim_start_matches('=').rsplit_once('!')?;
+    let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(quoted) => quoted.replace("''", "'"),
+        None => sheet.to_string(),
+    };
+    let (start, end) = parse_cells_ref(cells)?;
+    Some(CellArea { sheet, start, end })
+}
+
+
+/// Parses "B3:E7", "D4" or whole columns like "A:C" into 0-based `(row, column)` corners.
+pub fn parse_cells_ref(cells: &str) -> Option<((u32, u32), (u32, u32))> {
+    let (first, last) = cells.trim().split_once(':').unwrap_or((cells.trim(), cells.trim()));
+    let column = |letters: &str| parse_cell_ref(&format!("{}1", letters)).map(|(_, col)| col);
+    let (start, end) = match (parse_cell_ref(first), parse_cell_ref(last)) {
+        (Some(start), Some(end)) => (start, end),
+        // Column letters alone, e.g. "$A:$C"
+        (None, None) => ((0, column(first)?), (LAST_ROW, column(last)?)),
+        _ => return None,
+    };
+    Some(((start.0.min(end.0), start.1.min(end.1)), (start.0.max(end.0), start.1.max(end.1))))
+}
+
+
+/// Cuts the cells between two 0-based corners out of a worksheet range.
+///
+/// Rows below the sheet's data are left out, and whole-column areas start at
+/// the sheet's first used row, so "A:C" covers exactly the rows in use.
+pub fn slice_range(range: &Range<Data>, start: (u32, u32), end: (u32, u32)) -> Range<Data> {
+    let (Some(used_start), Some(used_end)) = (range.start(), range.end()) else {
+        return Range::empty();
+    };
+    let first_row = match (start.0, end.0) {
+        (0, LAST_ROW) => used_start.0,
+        _ => start.0,
+    };
+    let last_row = end.0.min(used_end.0);
+    if last_row < first_row {
+        return Range::empty();
+    }
+    range.range((first_row, start.1), (last_row, end.1))
+}
+
+
+/// The 0-based corners of the largest block of touching non-empty cells, for `--anchor auto`.
+///
+/// Cells touch across edges and corners. Smaller blocks are taken into the
+/// largest one when they overlap its bounding box, or when they sit beside
+/// it within its rows with at most one empty column between, so an empty
+/// column doesn't cut a table in two. Stray cells elsewhere, like a title
+/// above the table, stay out.
+pub fn largest_block(range: &Range<Data>) -> Option<((u32, u32), (u32, u32))> {
+    let (start_row, start_col) = range.start()?;
+    let (height, width) = range.get_size();
+    let filled = |row: usize, col: usize| match range.get((row, col)) {
+        None | Some(Data::Empty) => false,
+        Some(Data::String(text)) => !text.trim().is_empty(),
+        Some(_) => true,
+    };
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut seen = vec![false; height * width];
+    for row in 0..height {
+        for col in 0..width {
+            if seen[row * width + col] || !filled(row, col) {
+                continue;
+            }
+            seen[row * width + col] = true;
+            let (mut count, mut min, mut max) = (0, (row, col), (row, col));
+            let mut stack = vec![(row, col)];
+            while let Some((r, c)) = stack.pop() {
+                count += 1;
+                min = (min.0.min(r), min.1.min(c));
+                max = (max.0.max(r), max.1.max(c));
+                for nr in r.saturating_sub(1)..=(r + 1).min(height - 1) {
+                    for nc in c.saturating_sub(1)..=(c + 1).min(width - 1) {
+                        if !seen[nr * width + nc] && filled(nr, nc) {
+                            seen[nr * width + nc] = true;
+                            stack.push((nr, nc));
+                        }
+                    }
+                }
+            }
+            blocks.push(Block { cells: count, min, max });
+        }
+    }
+    // The first of equally large blocks, i.e. the top-most
+    let largest = (0..blocks.len()).rev().max_by_key(|&i| blocks[i].cells)?;
+    let Block { mut min, mut max, .. } = blocks.swap_remove(largest);
+    loop {
+        let joins = |block: &Block| {
+            let ((top, left), (bottom, right)) = (block.min, block.max);
+            let overlaps = top <= max.0 && bottom >= min.0 && left <= max.1 && right >= min.1;
+            let beside = top >= min.0 && bottom <= max.0 && left <= max.1 + 2 && right + 2 >= min.1;
+            overlaps || beside
+        };
+        let Some(i) = blocks.iter().position(joins) else { break };
+        let block = blocks.swap_remove(i);
+        min = (min.0.min(block.min.0), min.1.min(block.min.1));
+        max = (max.0.max(block.max.0), max.1.max(block.max.1));
+    }
+    let corner = |(row, col): (usize, usize)| (start_row + row as u32, start_col + col as u32);
+    Some((corner(min), corner(max)))
+}
+
+/// Touching non-empty cells found by `largest_block`, with 0-based corners relative to the range.
+struct Block {
+    cells: usize,
+    min: (usize, usize),
+    max: (usize, usize),
+}
+
+
+/// Splits an A1-style reference like "D4" or "$D$4" into a 0-based `(row, column)`.
+///
+/// Columns past "XFD", the last one Excel has, are not a cell.
+pub fn parse_cell_ref(cell: &str) -> Option<(u32, u32)> {
+    let cell = cell.replace('$', "");
+    let split = cell.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = cell.split_at(split);
+    if letters.is_empty() {
+        return None;
+    }
+    let col = letters.chars().try_fold(0u32, |acc, c| {
+        let c = c.to_ascii_uppercase();
+        c.is_ascii_uppercase()
+            .then(|| acc.checked_mul(26)?.checked_add(c as u32 - 'A' as u32 + 1))
+            .flatten()
+            .filter(|&col| col <= LAST_COL + 1)
+    })?;
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, col - 1))
+}
+
+
+/// The merged cell blocks of the sheet called exactly `sheet`, in sheet order.
+pub fn merged_cells(workbook: &mut Xlsx<BufReader<File>>, sheet: &str) -> Result<Vec<CellArea>, Box<dyn Error>> {
+    let regions = workbook
+        .worksheet_merge_cells(sheet)
+        .ok_or_else(|| ExcelReaderError::SheetNotFound(sheet.to_string()))??;
+    Ok(regions
+        .into_iter()
+        .map(|region| CellArea { sheet: sheet.to_string(), start: region.start, end: region.end })
+        .collect())
+}
+
+
+impl CellArea {
+    /// The A1-style reference of the cells without the sheet, e.g. "B9:D9", or "B9" for a single cell.
+    pub fn cells_ref(&self) -> String {
+        let cell = |(row, col): (u32, u32)| format!("{}{}", column_letter(col), row + 1);
+        match self.start == self.end {
+            true => cell(self.start),
+            false => format!("{}:{}", cell(self.start), cell(self.end)),
+        }
+    }
+}
+
+
+/// The A1-style letters of a 0-based column index, e.g. 0 => "A" and 27 => "AB".
+pub fn column_letter(col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        letters.push(b'A' + ((n - 1) % 26) as u8);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().map(|&b| b as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheet_lookup_ignores_whitespace_differences() -> Result<(), Box<dyn Error>> {
+        let names = vec!["МАЙ  2024".to_string(), "Лист1".to_string()];
+        let exact = SheetMatch { exact: true, unicode: None };
+        assert_eq!(find_sheet_name(&names, "МАЙ  2024", exact)?, "МАЙ  2024");
+        assert_eq!(find_sheet_name(&names, "МАЙ 2024 ", SheetMatch::default())?, "МАЙ  2024");
+        assert!(find_sheet_name(&names, "МАЙ 2024", exact).is_err());
+        // "Й" written as "И" plus a combining breve
+        let decomposed = "МАЙ  2024".replace('\u{419}', "\u{418}\u{306}");
+        let unicode = SheetMatch { exact: true, unicode: Some(UnicodeForm::Nfc) };
+        assert!(find_sheet_name(&names, &decomposed, exact).is_err());
+        assert_eq!(find_sheet_name(&names, &decomposed, unicode)?, "МАЙ  2024");
+        Ok(())
+    }
+
+    #[test]
+    fn parses_defined_name_references() {
+        let area = parse_area_ref("'Q1 ''24'!$B$3:$E$7").unwrap();
+        assert_eq!(area.sheet, "Q1 '24");
+        assert_eq!((area.start, area.end), ((2, 1), (6, 4)));
+        assert_eq!(parse_area_ref("Data!AA10").map(|a| a.start), Some((9, 26)));
+        assert!(parse_area_ref("Data!#REF!").is_none());
+        assert_eq!(parse_cells_ref("$C:$A"), Some(((0, 0), (LAST_ROW, 2))));
+        assert!(parse_cells_ref("A1:C").is_none());
+        for col in [0, 25, 26, 701, 702, LAST_COL] {
+            let letters = column_letter(col);
+            assert_eq!(parse_cell_ref(&format!("{}1", letters)), Some((0, col)));
+        }
+        assert_eq!(column_letter(LAST_COL), "XFD");
+        // Past the last column, including letters that would overflow a u32
+        assert!(parse_cell_ref("XFE1").is_none());
+        assert!(parse_cell_ref("ZZZZZZZZZZZZZZ1").is_none());
+        assert!(parse_cells_ref("A:ZZZZZZZZZZZZZZ").is_none());
+    }
+
+    #[test]
+    fn auto_anchor_finds_the_largest_block() {
+        let text = |s: &str| Data::String(s.to_string());
+        let mut cells = vec![calamine::Cell::new((0, 0), text("Monthly report"))];
+        for row in 3..6 {
+            // Column 3 is empty, the table still spans columns 1 to 4
+            for col in [1, 2, 4] {
+                cells.push(calamine::Cell::new((row, col), text("x")));
+            }
+        }
+        let range = Range::from_sparse(cells);
+        assert_eq!(largest_block(&range), Some(((3, 1), (5, 4))));
+    }
+}